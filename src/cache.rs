@@ -0,0 +1,145 @@
+use crate::parse::{files_with_extension, parse_version};
+use crate::{parse_db, Db, ItemIndex, Usages};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Version of the cache file. Caches written with another version are parsed again.
+///
+/// Bump it whenever parsing changes in a way that makes old caches wrong, and whenever anything
+/// serialized in `Db` changes (a field is added, removed, renamed or changes type), or old caches
+/// will fail to load. `tests/cache.rs` checks the latter against `tests/cache_shape.txt`.
+pub const CACHE_FORMAT: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+    path: PathBuf,
+    size: u64,
+    // nanoseconds since the epoch
+    mtime: u128,
+    // crc32 of the content
+    hash: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cache {
+    format: u32,
+    crate_version: String,
+    game_version: String,
+    files: Vec<FileStamp>,
+    db: Db,
+}
+
+// every file parse_db can read
+fn input_files(game_path: &Path) -> Vec<PathBuf> {
+    let content = game_path.join("Content");
+    let mut result = vec![game_path.join("Barotrauma.deps.json")];
+    result.extend(files_with_extension(&content, "xml", true));
+    result.extend(files_with_extension(&content, "sub", true));
+    result
+}
+
+// the content is only hashed if size or mtime differ from the previous run
+fn stamp(path: &Path, previous: &HashMap<&Path, &FileStamp>) -> std::io::Result<FileStamp> {
+    let meta = std::fs::metadata(path)?;
+    let size = meta.len();
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let known = previous
+        .get(path)
+        .filter(|f| f.size == size && f.mtime == mtime);
+    let hash = match known {
+        Some(f) => f.hash,
+        None => crc32fast::hash(&std::fs::read(path)?),
+    };
+    Ok(FileStamp {
+        path: path.to_owned(),
+        size,
+        mtime,
+        hash,
+    })
+}
+
+fn load(cache_path: &Path, game_version: &str) -> Option<Cache> {
+    let content = std::fs::read(cache_path).ok()?;
+    let cache: Cache = match serde_json::from_slice(&content) {
+        Ok(x) => x,
+        Err(e) => {
+            log::debug!("ignoring unreadable cache {}: {}", cache_path.display(), e);
+            return None;
+        }
+    };
+    if cache.format != CACHE_FORMAT
+        || cache.crate_version != env!("CARGO_PKG_VERSION")
+        || cache.game_version != game_version
+    {
+        log::debug!("ignoring outdated cache {}", cache_path.display());
+        return None;
+    }
+    Some(cache)
+}
+
+fn save(cache_path: &Path, cache: &Cache) -> std::io::Result<()> {
+    if let Some(parent) = cache_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(cache_path, serde_json::to_vec(cache)?)
+}
+
+/// Same as [`parse_db`], but reuses the result of a previous run stored at `cache_path`
+/// as long as none of the game files changed and the game version is the same.
+/// Files are compared by path, size, modification time and content hash.
+/// Fails if a game file can't be read or the cache can't be written.
+pub fn parse_db_cached(game_path: &Path, cache_path: &Path) -> std::io::Result<Db> {
+    let game_version = parse_version(game_path);
+    let previous = load(cache_path, &game_version);
+    let previous_files = previous
+        .iter()
+        .flat_map(|c| &c.files)
+        .map(|f| (f.path.as_path(), f))
+        .collect::<HashMap<_, _>>();
+    let files = input_files(game_path)
+        .iter()
+        .map(|path| stamp(path, &previous_files))
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    if let Some(mut cache) = previous {
+        let same_content = |a: &FileStamp, b: &FileStamp| a.path == b.path && a.hash == b.hash;
+        if cache.files.len() == files.len()
+            && cache
+                .files
+                .iter()
+                .zip(&files)
+                .all(|(a, b)| same_content(a, b))
+        {
+            log::info!("using cached game data from {}", cache_path.display());
+            // files that were only touched don't need to be hashed again next time
+            if cache.files != files {
+                cache.files = files;
+                save(cache_path, &cache)?;
+            }
+            let mut db = cache.db;
+            db.usages = Usages::build(&db.items);
+            db.index = ItemIndex::build(&db.items, None);
+            return Ok(db);
+        }
+        log::info!("game files changed, parsing again");
+    }
+
+    let cache = Cache {
+        format: CACHE_FORMAT,
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        game_version,
+        files,
+        db: parse_db(game_path),
+    };
+    save(cache_path, &cache)?;
+    Ok(cache.db)
+}
@@ -0,0 +1,336 @@
+//! Crafting costs and buy/sell margins.
+
+use crate::{Fabricate, Item, LocationType, RequiredItem};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// How much it takes to fabricate a single unit of an item (i.e. already divided by the recipe's output amount)
+#[derive(Debug, Clone)]
+pub struct CraftingCost {
+    /// base price of all the non-craftable materials, after expanding every sub-recipe
+    pub raw_cost: f32,
+    /// non-craftable material id -> amount
+    pub raw_mats: BTreeMap<String, f32>,
+    /// cost if every material is either bought or fabricated, whichever is cheaper
+    pub cheapest_cost: f32,
+    /// materials (at any depth) that are cheaper to fabricate than to buy
+    pub fabricated_mats: BTreeSet<String>,
+}
+
+// cost and material amounts, see `CraftingCost`
+type Raw = (f32, BTreeMap<String, f32>);
+// cost and fabricated materials, see `CraftingCost`
+type Cheapest = (f32, BTreeSet<String>);
+
+struct Calculator<'a> {
+    items: HashMap<&'a str, &'a Item>,
+    // items currently being expanded, so that recipe cycles are bought instead of recursed into
+    stack: Vec<&'a str>,
+    // set when a recipe was skipped because of `stack`, results computed meanwhile depend on it
+    cut_cycle: bool,
+    // results per item id, only kept if no cycle was cut while computing them
+    raw_done: HashMap<&'a str, Option<Raw>>,
+    cheapest_done: HashMap<&'a str, Option<Cheapest>>,
+}
+
+impl<'a> Calculator<'a> {
+    fn new(items: &'a [Item]) -> Self {
+        Calculator {
+            // first definition wins, same as linkify_item
+            items: items.iter().rev().map(|it| (it.id.as_str(), it)).collect(),
+            stack: vec![],
+            cut_cycle: false,
+            raw_done: HashMap::new(),
+            cheapest_done: HashMap::new(),
+        }
+    }
+
+    // all the items a recipe slot can be filled with
+    fn candidates(&self, mat: &RequiredItem) -> Vec<&'a Item> {
+        let mut found = match mat {
+            RequiredItem::Id(id) => self.items.get(id.as_str()).copied().into_iter().collect(),
+            RequiredItem::Tag(tag) => self
+                .items
+                .values()
+                .filter(|it| it.tags.contains(tag))
+                .copied()
+                .collect::<Vec<_>>(),
+        };
+        found.sort_by(|a, b| a.id.cmp(&b.id));
+        found
+    }
+
+    fn recipe(&mut self, item: &'a Item) -> Option<&'a Fabricate> {
+        if self.stack.contains(&item.id.as_str()) {
+            self.cut_cycle = true;
+            return None;
+        }
+        item.fabricate.as_ref()
+    }
+
+    // None if some material has no price (i.e. is not in the item list at all)
+    fn raw(&mut self, item: &'a Item) -> Option<Raw> {
+        if let Some(done) = self.raw_done.get(item.id.as_str()) {
+            return done.clone();
+        }
+        let outer_cut = std::mem::replace(&mut self.cut_cycle, false);
+        let result = self.raw_uncached(item);
+        if !self.cut_cycle {
+            self.raw_done.insert(&item.id, result.clone());
+        }
+        self.cut_cycle |= outer_cut;
+        result
+    }
+
+    fn raw_uncached(&mut self, item: &'a Item) -> Option<Raw> {
+        let fab = match self.recipe(item) {
+            None => {
+                let mut mats = BTreeMap::new();
+                mats.insert(item.id.clone(), 1.0);
+                return Some((item.prices.base_price as f32, mats));
+            }
+            Some(f) => f,
+        };
+        self.stack.push(&item.id);
+        let mut total = 0.0;
+        let mut total_mats = BTreeMap::new();
+        let mut ok = true;
+        for (mat, cnt) in &fab.mats {
+            let per_unit = *cnt as f32 / fab.out_amount as f32;
+            let best = self
+                .candidates(mat)
+                .into_iter()
+                .filter_map(|cand| self.raw(cand))
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            match best {
+                Some((cost, mats)) => {
+                    total += cost * per_unit;
+                    for (id, amount) in mats {
+                        *total_mats.entry(id).or_insert(0.0) += amount * per_unit;
+                    }
+                }
+                None => ok = false,
+            }
+        }
+        self.stack.pop();
+        if ok {
+            Some((total, total_mats))
+        } else {
+            None
+        }
+    }
+
+    fn craft_cheapest(&mut self, item: &'a Item) -> Option<Cheapest> {
+        if let Some(done) = self.cheapest_done.get(item.id.as_str()) {
+            return done.clone();
+        }
+        let outer_cut = std::mem::replace(&mut self.cut_cycle, false);
+        let result = self.craft_cheapest_uncached(item);
+        if !self.cut_cycle {
+            self.cheapest_done.insert(&item.id, result.clone());
+        }
+        self.cut_cycle |= outer_cut;
+        result
+    }
+
+    fn craft_cheapest_uncached(&mut self, item: &'a Item) -> Option<Cheapest> {
+        let fab = self.recipe(item)?;
+        self.stack.push(&item.id);
+        let mut total = 0.0;
+        let mut fabricated = BTreeSet::new();
+        let mut ok = true;
+        for (mat, cnt) in &fab.mats {
+            let per_unit = *cnt as f32 / fab.out_amount as f32;
+            let mut best: Option<Cheapest> = None;
+            for cand in self.candidates(mat) {
+                let buy = (cand.prices.base_price as f32, BTreeSet::new());
+                let option = match self.craft_cheapest(cand) {
+                    Some((cost, mut path)) if cost < buy.0 => {
+                        path.insert(cand.id.clone());
+                        (cost, path)
+                    }
+                    _ => buy,
+                };
+                if best.as_ref().map(|b| option.0 < b.0).unwrap_or(true) {
+                    best = Some(option);
+                }
+            }
+            match best {
+                Some((cost, path)) => {
+                    total += cost * per_unit;
+                    fabricated.extend(path);
+                }
+                None => ok = false,
+            }
+        }
+        self.stack.pop();
+        if ok {
+            Some((total, fabricated))
+        } else {
+            None
+        }
+    }
+}
+
+/// Computes the crafting cost of every fabricable item, keyed by item id.
+/// Items with a material that can not be priced are left out.
+pub fn crafting_costs(items: &[Item]) -> BTreeMap<String, CraftingCost> {
+    let mut calc = Calculator::new(items);
+    let mut result = BTreeMap::new();
+    for item in items.iter().filter(|it| it.fabricate.is_some()) {
+        if result.contains_key(&item.id) {
+            continue;
+        }
+        let raw = calc.raw(item);
+        let cheapest = calc.craft_cheapest(item);
+        match (raw, cheapest) {
+            (Some((raw_cost, raw_mats)), Some((cheapest_cost, fabricated_mats))) => {
+                result.insert(
+                    item.id.clone(),
+                    CraftingCost {
+                        raw_cost,
+                        raw_mats,
+                        cheapest_cost,
+                        fabricated_mats,
+                    },
+                );
+            }
+            _ => log::debug!("can't compute crafting cost of {}", item.id),
+        }
+    }
+    result
+}
+
+/// How money is made out of an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// buy the item from the store and sell what it deconstructs into
+    BuyDeconstruct,
+    /// buy the materials from the store and sell the fabricated item
+    FabricateSell,
+}
+impl Route {
+    /// Short name, for csv output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Route::BuyDeconstruct => "buy_deconstruct",
+            Route::FabricateSell => "fabricate_sell",
+        }
+    }
+}
+
+/// The margin of a route for an item at a location type.
+#[derive(Debug, Clone)]
+pub struct Arbitrage {
+    /// location type where everything is bought and sold
+    pub location: String,
+    /// what is done with the item
+    pub route: Route,
+    /// item that is deconstructed or fabricated
+    pub item_id: String,
+    /// money spent
+    pub cost: f32,
+    /// store price of what is got
+    pub value: f32,
+    /// money the store pays for what is got, i.e. `value` with its sell price modifier applied
+    pub revenue: f32,
+}
+impl Arbitrage {
+    /// Value minus cost, i.e. what the route would make if the store paid full price.
+    pub fn margin(&self) -> f32 {
+        self.value - self.cost
+    }
+    /// Revenue minus cost.
+    pub fn profit(&self) -> f32 {
+        self.revenue - self.cost
+    }
+    /// Everything is bought from and sold to the same store, so a route that makes money even at the store's
+    /// buy-back price can be repeated indefinitely.
+    pub fn is_money_loop(&self) -> bool {
+        self.profit() > 0.0
+    }
+}
+
+/// Computes the buy-and-deconstruct and fabricate-and-sell margins of every item at every location type.
+/// What is sold goes for the store price times the location type's sell price modifier;
+/// location types not in `location_types` use `LocationType::DEFAULT_SELL_PRICE_MODIFIER`.
+/// Routes that need something the store doesn't sell, or that produce something without a price, are left out.
+pub fn arbitrage(items: &[Item], location_types: &[LocationType]) -> Vec<Arbitrage> {
+    let calc = Calculator::new(items);
+    let locations = items
+        .iter()
+        .flat_map(|item| item.prices.locations.keys())
+        .collect::<BTreeSet<_>>();
+
+    let mut result = vec![];
+    for loc in locations {
+        let sell_price_modifier = location_types
+            .iter()
+            .find(|lt| &lt.id == loc)
+            .map_or(LocationType::DEFAULT_SELL_PRICE_MODIFIER, |lt| {
+                lt.sell_price_modifier
+            });
+        let mut seen = BTreeSet::new();
+        for item in items {
+            if !seen.insert(item.id.as_str()) {
+                continue;
+            }
+            if let Some(decon) = item.deconstruct.as_ref() {
+                let value = decon
+                    .mats
+                    .iter()
+                    .map(|(mat_id, cnt)| {
+                        calc.items
+                            .get(mat_id.as_str())
+                            .map(|mat| mat.prices.price_at(loc) * *cnt as f32)
+                    })
+                    .sum::<Option<f32>>();
+                if let (Some(value), true) = (value, item.prices.at(loc).1) {
+                    if !decon.mats.is_empty() {
+                        result.push(Arbitrage {
+                            location: loc.clone(),
+                            route: Route::BuyDeconstruct,
+                            item_id: item.id.clone(),
+                            cost: item.prices.price_at(loc),
+                            value,
+                            revenue: value * sell_price_modifier,
+                        });
+                    }
+                }
+            }
+            if let Some(fab) = item.fabricate.as_ref() {
+                let cost = fab
+                    .mats
+                    .iter()
+                    .map(|(mat, cnt)| {
+                        calc.candidates(mat)
+                            .into_iter()
+                            .filter(|cand| cand.prices.at(loc).1)
+                            .map(|cand| cand.prices.price_at(loc))
+                            .min_by(|a, b| a.partial_cmp(b).unwrap())
+                            .map(|price| price * *cnt as f32)
+                    })
+                    .sum::<Option<f32>>();
+                if let Some(cost) = cost {
+                    let value = item.prices.price_at(loc) * fab.out_amount as f32;
+                    result.push(Arbitrage {
+                        location: loc.clone(),
+                        route: Route::FabricateSell,
+                        item_id: item.id.clone(),
+                        cost,
+                        value,
+                        revenue: value * sell_price_modifier,
+                    });
+                }
+            }
+        }
+    }
+    result.sort_by(|a, b| {
+        b.profit()
+            .partial_cmp(&a.profit())
+            .unwrap()
+            .then_with(|| a.item_id.cmp(&b.item_id))
+            .then_with(|| a.location.cmp(&b.location))
+    });
+    result
+}
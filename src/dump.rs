@@ -1,9 +1,11 @@
-use crate::{Db, Item, RequiredItem};
+use barostuff::{
+    Creature, Db, Fabricate, Item, Job, RecipeUnlock, RequiredItem, TalentSubTree, UnknownItem,
+};
 
 use std::io::Write;
 use std::path::Path;
 
-pub(crate) fn dump_prices(items: &[Item]) {
+pub(crate) fn dump_prices(db: &Db) {
     let out_path = Path::new("out/items_prices.csv");
     std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
     let out_file = std::fs::OpenOptions::new()
@@ -13,15 +15,15 @@ pub(crate) fn dump_prices(items: &[Item]) {
         .open(out_path)
         .unwrap();
     let mut writer = csv::Writer::from_writer(out_file);
-    let all_locations = items
-        .iter()
+    let all_locations = db
+        .selected_items()
         .flat_map(|item| item.prices.locations.keys())
         .collect::<std::collections::BTreeSet<_>>();
     let all_locations = all_locations.into_iter().collect::<Vec<_>>();
     writer
         .write_record(std::iter::once("name").chain(all_locations.iter().map(|x| x.as_str())))
         .unwrap();
-    for item in items {
+    for item in db.selected_items() {
         let mut record = vec![];
         record.push(item.id.to_string());
         // println!("{:?} {:?}", item, all_locations);
@@ -43,14 +45,26 @@ pub(crate) fn dump_prices(items: &[Item]) {
 // Wiki is inconsisntent in whether ItemName.png represents the former or the latter
 // So sometimes we need to postfix the filename with _icon to get the in-inventory look.
 // This function denotes the several exeptions that do require such postfixing.
-fn is_iconic_item(item: &Item) -> bool {
-    return false;
+fn is_iconic_item(_item: &Item) -> bool {
+    false
+}
+
+fn linkify_item(db: &Db, id: &str, cnt: i32, size: Option<i32>) -> Result<String, UnknownItem> {
+    let item = db.try_item(id)?;
+    let mut line = if let Some(name) = item.name.as_deref() {
+        link_name(name, size)
+    } else {
+        // no name, no wiki page to link to
+        id.to_string()
+    };
+    if cnt > 1 {
+        line += &format!(" (x{})", cnt);
+    }
+    Ok(line)
 }
 
-fn linkify_item(items: &[Item], id: &str, cnt: i32, size: Option<i32>) -> String {
-    let item = items.iter().find(|it| &it.id == id).unwrap();
-    let name = item.name.as_deref().unwrap();
-    let mut line = if let Some(size) = size {
+fn link_name(name: &str, size: Option<i32>) -> String {
+    if let Some(size) = size {
         format!(
             "{{{{Hyperlink|{name}|{size}px}}}}",
             name = name,
@@ -58,14 +72,39 @@ fn linkify_item(items: &[Item], id: &str, cnt: i32, size: Option<i32>) -> String
         )
     } else {
         format!("{{{{Hyperlink|{name}}}}}", name = name)
+    }
+}
+
+// any wire will do, but the plain one is what the wiki calls Wire.
+// other tags list every item that has them
+fn linkify_tag(db: &Db, tag: &str, cnt: i32, size: Option<i32>) -> Result<String, UnknownItem> {
+    if tag == "wire" {
+        return linkify_item(db, "wire", cnt, size);
+    }
+    let links = db
+        .items_with_tag(tag)
+        .map(|it| linkify_item(db, &it.id, 1, size))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut line = if links.is_empty() {
+        format!("any ''{}''", tag)
+    } else {
+        links.join(" or ")
     };
     if cnt > 1 {
         line += &format!(" (x{})", cnt);
     }
-    line
+    Ok(line)
+}
+
+// None for items that don't exist or have no name, and so no wiki page
+fn linkify_named_item(db: &Db, id: &str, cnt: i32, size: Option<i32>) -> Option<String> {
+    if !db.has_named_item(id) {
+        return None;
+    }
+    linkify_item(db, id, cnt, size).ok()
 }
 
-pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<()> {
+pub(crate) fn dump_fabricate(db: &Db, fab_type: &str) -> std::io::Result<()> {
     let out_path = Path::new(&format!("out/fabricate_{}.txt", fab_type)).to_owned();
     std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
     let mut file = std::fs::OpenOptions::new()
@@ -75,7 +114,7 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
         .open(out_path)
         .unwrap();
 
-    file.write(
+    file.write_all(
         r#"{| class="wikitable sortable" style="width: 50%; font-size: 90%;"
 ! style="width: 15%" | Item
 ! style="width: 30%" | Materials to Craft 
@@ -86,8 +125,8 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
         .as_bytes(),
     )?;
 
-    let mut items = items.to_vec();
-    items.sort_by_key(|i| i.name.clone());
+    let mut items = db.items_made_in(fab_type).collect::<Vec<_>>();
+    items.sort_by_key(|i| &i.name);
 
     // categories, exceptions, name
     let grouped_category_tags: Vec<(Vec<&str>, Vec<&str>, &str)> = vec![
@@ -99,7 +138,7 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
         (vec!["sensor"], vec![], "[[File:Detectors.png| |90px|link=Detectors]] <br> [[Detectors]]",),
         (vec!["wire"], vec![], "[[File:Wire.png| |50px|link=Wire]] <br> [[Wire]]"),
     ];
-    let blacklist = vec!["lightcomponent90"];
+    let blacklist = ["lightcomponent90"];
 
     let make_item_line = |item: &Item, name_override: Option<&str>| {
         // debug!("{:?}", item.id);
@@ -108,16 +147,10 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
             .mats
             .iter()
             .map(|(m, cnt)| match m {
-                RequiredItem::Id(id) => linkify_item(&items, id, *cnt, Some(30)),
-                RequiredItem::Tag(tag) => match tag.as_str() {
-                    "wire" => {
-                        // "{{{{Hyperlink| Wire|30px|}}}} (any)".to_string(),
-                        linkify_item(&items, "wire", *cnt, Some(30))
-                    }
-                    _ => panic!("{:?}", tag),
-                },
+                RequiredItem::Id(id) => linkify_item(db, id, *cnt, Some(30)),
+                RequiredItem::Tag(tag) => linkify_tag(db, tag, *cnt, Some(30)),
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, _>>()?;
         let fabricate_line = fabricate_mat_names.join(" <br> ");
 
         let decon_line = match item.deconstruct.as_ref() {
@@ -139,8 +172,8 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
                 } else {
                     d.mats
                         .iter()
-                        .map(|(mat_id, cnt)| linkify_item(&items, mat_id, *cnt, Some(30)))
-                        .collect::<Vec<_>>()
+                        .map(|(mat_id, cnt)| linkify_item(db, mat_id, *cnt, Some(30)))
+                        .collect::<Result<Vec<_>, _>>()?
                         .join(" <br> ")
                 }
             }
@@ -148,20 +181,10 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
         let mut skills = fabricate
             .skills
             .iter()
-            .map(|(id, level)| {
-                let name = match id.as_str() {
-                    "electrical" => "Electrical",
-                    "helm" => "Helm",
-                    "mechanical" => "Mechanical",
-                    "medical" => "Medical",
-                    "weapons" => "Weapons",
-                    _ => panic!("{:?}", fabricate),
-                };
-                format!("{} {}", name, level)
-            })
+            .map(|(id, level)| format!("{} {}", db.localization.skill_name(id), level))
             .collect::<Vec<_>>()
             .join(" <br> ");
-        if skills == "" {
+        if skills.is_empty() {
             skills = "None".into()
         }
 
@@ -169,16 +192,20 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
             no.to_string()
         } else {
             let item_name = item.name.as_ref().unwrap().as_str();
-            let pic_name = if is_iconic_item(&item) {
+            let pic_name = if is_iconic_item(item) {
                 format!("{}_icon", item_name)
             } else {
                 item_name.to_string()
             };
-            format!(
+            let mut display_name = format!(
                 "[[File:{pic_name}.png| |50px|link={name}]] <br> [[{name}]]",
                 pic_name = pic_name,
                 name = item_name
-            )
+            );
+            if let Some(unlocks) = format_recipe_unlocks(fabricate, db, " <br> ")? {
+                display_name += &format!(" <br> <small>{}</small>", unlocks);
+            }
+            display_name
         };
 
         let line = format!(
@@ -200,18 +227,13 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
             time = fabricate.time,
             skills = skills,
         );
-        line
+        Ok::<_, UnknownItem>(line)
     };
 
-    for item in &items {
+    for &item in &items {
         if blacklist.contains(&item.id.as_str()) {
             continue;
         };
-        let fabricate = match &item.fabricate {
-            None => continue,
-            Some(f) => f,
-        };
-
         if item.tags.iter().any(|item_tag| {
             grouped_category_tags
                 .iter()
@@ -219,27 +241,16 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
         }) {
             continue;
         }
-        if fabricate.fabricator != fab_type {
-            continue;
-        };
-
-        let line = make_item_line(&item, None);
-        file.write(line.as_bytes())?;
+        let line = make_item_line(item, None)?;
+        file.write_all(line.as_bytes())?;
     }
 
     for (gc_tags, gc_exceptions, gc_name) in grouped_category_tags {
         let mut canonical_line = None;
-        for item in &items {
+        for &item in &items {
             if gc_exceptions.contains(&item.id.as_str()) {
                 continue;
             }
-            if let Some(f) = item.fabricate.as_ref() {
-                if f.fabricator != fab_type {
-                    continue;
-                }
-            } else {
-                continue;
-            }
             if !item
                 .tags
                 .iter()
@@ -253,7 +264,7 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
                 fab.mats.sort();
             }
 
-            let this_line = make_item_line(&fake_item, Some(gc_name));
+            let this_line = make_item_line(&fake_item, Some(gc_name))?;
             if let Some(cl) = canonical_line.as_ref() {
                 assert_eq!(&this_line, cl)
             } else {
@@ -278,7 +289,7 @@ pub(crate) fn dump_fabricate(items: &[Item], fab_type: &str) -> std::io::Result<
 
 // TERRIBLE TERRIBLE COPY-PASTE
 // but hopefully this is readable than even more ifs?..
-pub(crate) fn dump_deconstruct(items: &[Item]) -> std::io::Result<()> {
+pub(crate) fn dump_deconstruct(db: &Db) -> std::io::Result<()> {
     let out_path = Path::new("out/fabricate_deconstruct.txt");
     std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
     let mut file = std::fs::OpenOptions::new()
@@ -288,7 +299,7 @@ pub(crate) fn dump_deconstruct(items: &[Item]) -> std::io::Result<()> {
         .open(out_path)
         .unwrap();
 
-    file.write(
+    file.write_all(
         r#"{| class="wikitable sortable" style="width: 30%; font-size: 90%;"
 ! style="width: 40%" | Item
 ! style="width: 20%" | Time (seconds)
@@ -297,10 +308,7 @@ pub(crate) fn dump_deconstruct(items: &[Item]) -> std::io::Result<()> {
         .as_bytes(),
     )?;
 
-    let mut items = items.to_vec();
-    items.sort_by_key(|i| i.name.clone());
-
-    let blacklist = vec!["wire", "psilotoadegg", "balloonegg", "orangeboyegg"];
+    let blacklist = ["wire", "psilotoadegg", "balloonegg", "orangeboyegg"];
 
     let make_item_line = |item: &Item, name_override: Option<&str>| {
         // debug!("{:?}", item.id);
@@ -309,15 +317,15 @@ pub(crate) fn dump_deconstruct(items: &[Item]) -> std::io::Result<()> {
         let decon_line = decon
             .mats
             .iter()
-            .map(|(mat_id, cnt)| linkify_item(&items, mat_id, *cnt, Some(30)))
-            .collect::<Vec<_>>()
+            .map(|(mat_id, cnt)| linkify_item(db, mat_id, *cnt, Some(30)))
+            .collect::<Result<Vec<_>, _>>()?
             .join(" <br> ");
 
         let display_name = if let Some(no) = name_override {
             no.to_string()
         } else {
             let item_name = item.name.as_ref().unwrap().as_str();
-            let pic_name = if is_iconic_item(&item) {
+            let pic_name = if is_iconic_item(item) {
                 format!("{}_icon", item_name)
             } else if item.id == "smallmudraptoregg" {
                 // AAAAAA
@@ -344,10 +352,10 @@ pub(crate) fn dump_deconstruct(items: &[Item]) -> std::io::Result<()> {
             time = decon.time,
             deconstruct = decon_line,
         );
-        line
+        Ok::<_, UnknownItem>(line)
     };
 
-    for item in &items {
+    for item in db.items_by_name() {
         if blacklist.contains(&item.id.as_str()) {
             continue;
         }
@@ -362,7 +370,7 @@ pub(crate) fn dump_deconstruct(items: &[Item]) -> std::io::Result<()> {
             continue;
         }
 
-        let line = make_item_line(&item, None);
+        let line = make_item_line(item, None)?;
         file.write_all(line.as_bytes())?;
     }
 
@@ -375,7 +383,34 @@ pub(crate) fn dump_deconstruct(items: &[Item]) -> std::io::Result<()> {
     Ok(())
 }
 
-fn format_mineral(item: &Item) -> String {
+// None if the recipe is available right away
+fn format_recipe_unlocks(
+    fab: &Fabricate,
+    db: &Db,
+    separator: &str,
+) -> Result<Option<String>, UnknownItem> {
+    if fab.unlocked_by.is_empty() {
+        return Ok(if fab.requires_recipe {
+            Some("Requires a recipe unlock".into())
+        } else {
+            None
+        });
+    }
+    let unlocks = fab
+        .unlocked_by
+        .iter()
+        .map(|unlock| match unlock {
+            RecipeUnlock::Talent(id) => {
+                Ok(format!("Unlocked by talent [[{}]]", db.talent_name(id)))
+            }
+            RecipeUnlock::Item(id) => Ok(format!("Unlocked by {}", linkify_item(db, id, 1, None)?)),
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .join(separator);
+    Ok(Some(unlocks))
+}
+
+fn format_mineral(item: &Item, db: &Db) -> String {
     let mut fields: Vec<(String, String)> = vec![];
     // fields.("{{Gatherable Materials
     fields.push(("name".into(), item.name.as_ref().unwrap().to_string()));
@@ -385,22 +420,18 @@ fn format_mineral(item: &Item) -> String {
         panic!();
     }
     let lr = item.level_resource.as_ref().unwrap();
-    if lr.comonness.len() == 0 {
+    if lr.comonness.is_empty() {
         fields.push(("comonness".into(), lr.comonness_default.to_string()))
     } else {
-        for (level, biome) in &[
-            ("coldcaverns", "coldcaverns"),
-            ("ridgebasic", "europanridge"),
-            ("plateaubasic", "theaphoticplateau"),
-            ("greatseabasic", "thegreatsea"),
-            ("wastesbasic", "hydrothermalwastes"),
-        ] {
-            let com = if level != &"coldcaverns" {
-                lr.comonness.get(*level).unwrap()
-            } else {
-                lr.comonness.get(*level).unwrap_or(&lr.comonness_default)
+        for biome in db.biomes.iter().map(|b| b.id.as_str()) {
+            let com = match db.biome_commonness(lr, biome) {
+                Some(c) => c,
+                None => continue,
             };
-            fields.push((format!("comonness_{}", biome), (com * 100.0).round().to_string()));
+            fields.push((
+                format!("comonness_{}", biome),
+                (com * 100.0).round().to_string(),
+            ));
         }
     }
 
@@ -417,24 +448,27 @@ fn format_mineral(item: &Item) -> String {
     result
 }
 
-fn format_infobox(item: &Item, db: &Db) -> Option<String> {
+fn format_infobox(item: &Item, db: &Db) -> Result<Option<String>, UnknownItem> {
     let name = item.name.clone().unwrap();
     let mut category = None;
     if item.tags.contains(&"ore".into()) {
         category = Some("ore");
     }
-    let category = category?;
+    let category = match category {
+        Some(c) => c,
+        None => return Ok(None),
+    };
 
     let mut fields: Vec<(String, String)> = vec![];
     fields.push(("identifier".into(), item.id.clone()));
     fields.push(("name".into(), name.clone()));
     if category == "ore" {
         fields.push(("image".into(), format!("{}.png", name)));
-        // fields.push(("caption".into(), "Mined mineral".into()));
-        fields.push((
-            "caption".into(),
-            format!("''{}''", db.localization.item_description(&item.id)),
-        ));
+        let caption = match db.localization.item_description(&item.id) {
+            Some(description) => format!("''{}''", description),
+            None => "Mined mineral".into(),
+        };
+        fields.push(("caption".into(), caption));
         fields.push(("image2".into(), format!("{}_Mineral.png", name)));
         fields.push(("caption2".into(), "Sprite in the environment".into()));
         fields.push(("icon".into(), format!("{}.png", name)));
@@ -457,8 +491,8 @@ fn format_infobox(item: &Item, db: &Db) -> Option<String> {
     if !is_sold_anywhere {
         fields.push(("unbuyable".into(), "true".into()));
     }
-    for loc in &["outpost", "city", "research", "military", "mine"] {
-        let (mult, is_sold_here) = item.prices.locations.get(&loc.to_string()).unwrap();
+    for loc in db.store_location_types().iter().map(|lt| &lt.id) {
+        let (mult, is_sold_here) = item.prices.at(loc);
         fields.push((format!("{}multiplier", loc), format!("{}", mult)));
         if is_sold_anywhere && !is_sold_here {
             fields.push((format!("{}unbuyable", loc), "true".into()));
@@ -478,7 +512,7 @@ fn format_infobox(item: &Item, db: &Db) -> Option<String> {
         fields.push(("fabricatortime".into(), fab.time.to_string()));
         // TODO: only first skill is used. Others are ignored (only relevant for health scanner?)
         assert!(fab.skills.len() <= 1);
-        if let Some((skill, level)) = fab.skills.get(0) {
+        if let Some((skill, level)) = fab.skills.first() {
             fields.push(("fabricatorskill".into(), skill.to_string()));
             fields.push(("fabricatorskilllevel".into(), level.to_string()));
         }
@@ -487,15 +521,18 @@ fn format_infobox(item: &Item, db: &Db) -> Option<String> {
             .mats
             .iter()
             .map(|(mat, cnt)| match mat {
-                RequiredItem::Id(id) => linkify_item(&db.items, id, *cnt, None),
-                RequiredItem::Tag(tag) => match tag.as_str() {
-                    "wire" => linkify_item(&db.items, "wire", *cnt, None),
-                    _ => panic!("{:?}", tag),
-                },
+                RequiredItem::Id(id) => linkify_item(db, id, *cnt, None),
+                RequiredItem::Tag(tag) => linkify_tag(db, tag, *cnt, None),
             })
-            .collect::<Vec<_>>()
+            .collect::<Result<Vec<_>, _>>()?
             .join("\n");
         fields.push(("fabricatormaterials".into(), mats));
+        if fab.requires_recipe || !fab.unlocked_by.is_empty() {
+            fields.push(("requiresrecipe".into(), "true".into()));
+        }
+        if let Some(unlocks) = format_recipe_unlocks(fab, db, "\n")? {
+            fields.push(("unlockedby".into(), unlocks));
+        }
     }
 
     if let Some(decon) = item.deconstruct.as_ref() {
@@ -504,8 +541,8 @@ fn format_infobox(item: &Item, db: &Db) -> Option<String> {
         let mats = decon
             .mats
             .iter()
-            .map(|(mat_id, cnt)| linkify_item(&db.items, mat_id, *cnt, None))
-            .collect::<Vec<_>>()
+            .map(|(mat_id, cnt)| linkify_item(db, mat_id, *cnt, None))
+            .collect::<Result<Vec<_>, _>>()?
             .join("\n");
         fields.push(("deconstructormaterials".into(), mats));
     }
@@ -515,19 +552,19 @@ fn format_infobox(item: &Item, db: &Db) -> Option<String> {
         result += "{{Main|Minerals}}\n\n";
         result += &format!("{{{{Version|{}}}}}\n", db.version);
     }
-    result += &"{{Items infobox";
+    result += "{{Items infobox";
     for (k, v) in fields {
         result += &format!("\n| {} = {}", k, v);
     }
     result += "\n}}";
     if category == "ore" {
         result += "\n";
-        result += &format_mineral(item);
+        result += &format_mineral(item, db);
     }
-    Some(result)
+    Ok(Some(result))
 }
 
-pub(crate) fn dump_infoboxes(db: &Db) {
+pub(crate) fn dump_infoboxes(db: &Db) -> std::io::Result<()> {
     let out_path = Path::new("out/infoboxes.txt");
     std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
     let mut file = std::fs::OpenOptions::new()
@@ -536,18 +573,1169 @@ pub(crate) fn dump_infoboxes(db: &Db) {
         .write(true)
         .open(out_path)
         .unwrap();
-    for item in &db.items {
+    for item in db.selected_items() {
         let name = match item.name.as_ref() {
             Some(n) => n,
             None => continue,
         };
-        let ib = match format_infobox(item, &db) {
+        let ib = match format_infobox(item, db)? {
             Some(x) => x,
             None => continue,
         };
 
-        file.write_all(format!("\n\n ===  {}  ===  \n\n", name).as_bytes())
-            .unwrap();
-        file.write_all(ib.as_bytes()).unwrap();
+        file.write_all(format!("\n\n ===  {}  ===  \n\n", name).as_bytes())?;
+        file.write_all(ib.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn format_amount(amount: f32) -> String {
+    // two decimal places is plenty for wiki purposes, and trailing zeroes are just noise
+    let s = format!("{:.2}", amount);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+pub(crate) fn dump_crafting_costs(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/crafting_costs.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+
+    file.write_all(
+        r#"{| class="wikitable sortable" style="width: 70%; font-size: 90%;"
+! style="width: 15%" | Item
+! style="width: 10%" | Value
+! style="width: 25%" | Raw materials
+! style="width: 10%" | Raw materials cost
+! style="width: 10%" | Cheapest crafting cost
+! style="width: 20%" | <abbr title="Materials that are cheaper to fabricate than to buy">Fabricate instead of buying</abbr>
+! style="width: 10%" | Profit
+"#
+        .as_bytes(),
+    )?;
+
+    let costs = barostuff::cost::crafting_costs(&db.items);
+    // costs need every recipe, but only the selected items get a row
+    let mut rows = costs
+        .iter()
+        .filter(|(id, _)| db.is_selected(id))
+        .map(|(id, cost)| Ok((db.try_item(id)?, cost)))
+        .collect::<Result<Vec<_>, UnknownItem>>()?;
+    rows.retain(|(item, _)| item.name.is_some());
+    // most profitable first
+    rows.sort_by(|(a, a_cost), (b, b_cost)| {
+        let a_profit = a.prices.base_price as f32 - a_cost.cheapest_cost;
+        let b_profit = b.prices.base_price as f32 - b_cost.cheapest_cost;
+        b_profit
+            .partial_cmp(&a_profit)
+            .unwrap()
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    for (item, cost) in rows {
+        let raw_mats = cost
+            .raw_mats
+            .iter()
+            .map(|(mat_id, amount)| {
+                let mut line = linkify_item(db, mat_id, 1, Some(30))?;
+                if *amount != 1.0 {
+                    line += &format!(" (x{})", format_amount(*amount));
+                }
+                Ok(line)
+            })
+            .collect::<Result<Vec<_>, UnknownItem>>()?
+            .join(" <br> ");
+        let mut fabricated = cost
+            .fabricated_mats
+            .iter()
+            .map(|mat_id| linkify_item(db, mat_id, 1, Some(30)))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(" <br> ");
+        if fabricated.is_empty() {
+            fabricated = "-".into();
+        }
+        let line = format!(
+            r#"|-
+| align="center" | [[{name}]]
+| align="center" | {value}
+| align="left-index" | {raw_mats}
+| align="center" | {raw_cost}
+| align="center" | {cheapest_cost}
+| align="left-index" | {fabricated}
+| align="center" | {profit}
+"#,
+            name = item.name.as_ref().unwrap(),
+            value = item.prices.base_price,
+            raw_mats = raw_mats,
+            raw_cost = format_amount(cost.raw_cost),
+            cheapest_cost = format_amount(cost.cheapest_cost),
+            fabricated = fabricated,
+            profit = format_amount(item.prices.base_price as f32 - cost.cheapest_cost),
+        );
+        file.write_all(line.as_bytes())?;
+    }
+
+    file.write_all(
+        r#"|-
+|}
+"#
+        .as_bytes(),
+    )?;
+    Ok(())
+}
+
+pub(crate) fn dump_arbitrage(db: &Db) -> std::io::Result<()> {
+    let mut margins = barostuff::cost::arbitrage(&db.items, &db.location_types);
+    margins.retain(|m| db.is_selected(&m.item_id));
+
+    let csv_path = Path::new("out/arbitrage.csv");
+    std::fs::create_dir_all(csv_path.parent().unwrap()).unwrap();
+    let mut writer = csv::Writer::from_path(csv_path)?;
+    writer.write_record([
+        "location",
+        "route",
+        "item",
+        "cost",
+        "value",
+        "revenue",
+        "margin",
+        "profit",
+        "money_loop",
+    ])?;
+    for m in &margins {
+        writer.write_record([
+            m.location.clone(),
+            m.route.name().to_string(),
+            m.item_id.clone(),
+            format_amount(m.cost),
+            format_amount(m.value),
+            format_amount(m.revenue),
+            format_amount(m.margin()),
+            format_amount(m.profit()),
+            m.is_money_loop().to_string(),
+        ])?;
+    }
+    writer.flush()?;
+
+    let out_path = Path::new("out/arbitrage.txt");
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    file.write_all(
+        r#"{| class="wikitable sortable" style="width: 60%; font-size: 90%;"
+! style="width: 15%" | Location
+! style="width: 15%" | Method
+! style="width: 20%" | Item
+! style="width: 10%" | Cost
+! style="width: 10%" | <abbr title="Store price of what you end up with">Value</abbr>
+! style="width: 10%" | <abbr title="What the store pays for it">Revenue</abbr>
+! style="width: 10%" | Profit
+! style="width: 10%" | <abbr title="Bought and sold at the same store for a profit">Money loop</abbr>
+"#
+        .as_bytes(),
+    )?;
+    for m in &margins {
+        let item = db.try_item(&m.item_id)?;
+        if item.name.is_none() {
+            continue;
+        }
+        let line = format!(
+            r#"|-
+| align="center" | {location}
+| align="center" | {route}
+| align="left-index" | {item}
+| align="center" | {cost}
+| align="center" | {value}
+| align="center" | {revenue}
+| align="center" | {profit}
+| align="center" | {money_loop}
+"#,
+            location = m.location,
+            route = match m.route {
+                barostuff::cost::Route::BuyDeconstruct => "Buy and deconstruct",
+                barostuff::cost::Route::FabricateSell => "Fabricate and sell",
+            },
+            item = linkify_item(db, &m.item_id, 1, Some(30))?,
+            cost = format_amount(m.cost),
+            value = format_amount(m.value),
+            revenue = format_amount(m.revenue),
+            profit = format_amount(m.profit()),
+            money_loop = if m.is_money_loop() { "Yes" } else { "-" },
+        );
+        file.write_all(line.as_bytes())?;
+    }
+    file.write_all(
+        r#"|-
+|}
+"#
+        .as_bytes(),
+    )?;
+    Ok(())
+}
+
+/// Writes the material flow graph as both graphviz and graphml.
+/// With `root`, only what can be made out of that item is included.
+pub(crate) fn dump_recipe_graph(db: &Db, root: Option<&str>) -> std::io::Result<()> {
+    let items = db.selected_items().cloned().collect::<Vec<_>>();
+    let mut graph = barostuff::graph::RecipeGraph::build(&items);
+    let name = match root {
+        Some(root) => {
+            if !graph.nodes.contains_key(root) {
+                return Err(UnknownItem(root.to_string()).into());
+            }
+            graph = graph.subtree(root);
+            format!("recipes_{}", root)
+        }
+        None => "recipes".to_string(),
+    };
+    std::fs::create_dir_all("out")?;
+    std::fs::write(format!("out/{}.dot", name), graph.to_dot())?;
+    std::fs::write(format!("out/{}.graphml", name), graph.to_graphml())?;
+    Ok(())
+}
+
+/// Plain text report of material duplication loops, meant to be diffed between game versions.
+/// Loops are searched among all the items, but only the ones involving a selected item are written.
+pub(crate) fn dump_material_loops(db: &Db) -> std::io::Result<()> {
+    let format_mats = |mats: &std::collections::BTreeMap<String, f64>| {
+        mats.iter()
+            .map(|(id, amount)| format!("{} x{}", id, format_amount(*amount as f32)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let out_path = Path::new("out/material_loops.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    for l in barostuff::loops::find_material_loops(&db.items) {
+        if !l.steps.iter().any(|(_, id, _)| db.is_selected(id)) {
+            continue;
+        }
+        let steps = l
+            .steps
+            .iter()
+            .map(|(kind, id, times)| {
+                let action = match kind {
+                    barostuff::graph::EdgeKind::Fabricate => "fabricate",
+                    barostuff::graph::EdgeKind::Deconstruct => "deconstruct",
+                };
+                format!("{} {} x{}", action, id, format_amount(*times as f32))
+            })
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        log::warn!("material loop: {}", steps);
+        let text = format!(
+            "{}\n  consumed: {}\n  produced: {}\n  gain: {}\n\n",
+            steps,
+            format_mats(&l.consumed),
+            format_mats(&l.produced),
+            format_mats(&l.gain()),
+        );
+        file.write_all(text.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// "Used in" / "Obtained from" sections for every item page.
+pub(crate) fn dump_usages(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/usages.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+
+    for item in db.items_by_name() {
+        let name = match item.name.as_ref() {
+            Some(n) => n,
+            None => continue,
+        };
+        let format_list = |list: Vec<(&str, i32)>| {
+            list.into_iter()
+                .filter_map(|(id, cnt)| linkify_named_item(db, id, cnt, Some(30)))
+                .map(|link| format!("* {}\n", link))
+                .collect::<String>()
+        };
+        let used_in = format_list(db.used_in(&item.id));
+        let obtained_from = format_list(db.obtained_from(&item.id));
+        if used_in.is_empty() && obtained_from.is_empty() {
+            continue;
+        }
+
+        file.write_all(format!("\n\n ===  {}  ===  \n\n", name).as_bytes())?;
+        if !used_in.is_empty() {
+            file.write_all(format!("== Used in ==\n{}", used_in).as_bytes())?;
+        }
+        if !obtained_from.is_empty() {
+            if !used_in.is_empty() {
+                file.write_all(b"\n")?;
+            }
+            file.write_all(format!("== Obtained from ==\n{}", obtained_from).as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn format_attacks(creature: &Creature, db: &Db, separator: &str) -> String {
+    creature
+        .limbs
+        .iter()
+        .filter_map(|limb| limb.attack.as_ref().map(|a| (limb, a)))
+        .map(|(limb, attack)| {
+            let mut parts = attack
+                .afflictions
+                .iter()
+                .map(|(id, strength)| {
+                    format!("{} {}", strength, db.localization.affliction_name(id))
+                })
+                .collect::<Vec<_>>();
+            if attack.structure_damage > 0.0 {
+                parts.push(format!("{} structure damage", attack.structure_damage));
+            }
+            format!(
+                "{} (range {}): {}",
+                limb.name,
+                attack.range,
+                parts.join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn format_drops(creature: &Creature, db: &Db, size: Option<i32>, separator: &str) -> String {
+    creature
+        .inventory
+        .iter()
+        .filter_map(|(id, cnt)| {
+            let link = linkify_named_item(db, id, *cnt, size);
+            if link.is_none() {
+                log::debug!("{} drops unknown item {}", creature.id, id);
+            }
+            link
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn format_creature_infobox(creature: &Creature, db: &Db) -> String {
+    let name = creature.name.as_ref().unwrap();
+    let mut fields: Vec<(String, String)> = vec![];
+    fields.push(("identifier".into(), creature.id.clone()));
+    fields.push(("name".into(), name.clone()));
+    fields.push(("image".into(), format!("{}.png", name)));
+    if let Some(health) = creature.health {
+        fields.push(("health".into(), health.to_string()));
+    }
+    for (anim, speed) in &creature.speeds {
+        fields.push((format!("{}speed", anim), speed.to_string()));
+    }
+    fields.push(("limbs".into(), creature.limbs.len().to_string()));
+    let attacks = format_attacks(creature, db, "\n");
+    if !attacks.is_empty() {
+        fields.push(("attacks".into(), attacks));
+    }
+    let targets = creature
+        .ai_targets
+        .iter()
+        .map(|t| format!("{} ({}, priority {})", t.tag, t.state, t.priority))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !targets.is_empty() {
+        fields.push(("targets".into(), targets));
+    }
+    let drops = format_drops(creature, db, None, "\n");
+    if !drops.is_empty() {
+        fields.push(("drops".into(), drops));
+    }
+
+    let mut result = format!("{{{{Version|{}}}}}\n", db.version);
+    result += "{{Creature infobox";
+    for (k, v) in fields {
+        result += &format!("\n| {} = {}", k, v);
+    }
+    result += "\n}}";
+    result
+}
+
+pub(crate) fn dump_bestiary(db: &Db) -> std::io::Result<()> {
+    let mut creatures = db
+        .creatures
+        .iter()
+        .filter(|c| !c.has_info && c.name.is_some())
+        .collect::<Vec<_>>();
+    creatures.sort_by_key(|c| c.name.clone());
+
+    let out_path = Path::new("out/bestiary.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    file.write_all(
+        r#"{| class="wikitable sortable" style="width: 70%; font-size: 90%;"
+! style="width: 15%" | Creature
+! style="width: 10%" | Health
+! style="width: 15%" | Speed
+! style="width: 40%" | Attacks
+! style="width: 20%" | Drops
+"#
+        .as_bytes(),
+    )?;
+    for creature in &creatures {
+        let name = creature.name.as_ref().unwrap();
+        let speeds = creature
+            .speeds
+            .iter()
+            .map(|(anim, speed)| format!("{} {}", anim, speed))
+            .collect::<Vec<_>>()
+            .join(" <br> ");
+        let line = format!(
+            r#"|-
+| align="center" | [[File:{name}.png| |50px|link={name}]] <br> [[{name}]]
+| align="center" | {health}
+| align="center" | {speeds}
+| align="left-index" | {attacks}
+| align="left-index" | {drops}
+"#,
+            name = name,
+            health = creature
+                .health
+                .map(|h| h.to_string())
+                .unwrap_or_else(|| "-".into()),
+            speeds = speeds,
+            attacks = format_attacks(creature, db, " <br> "),
+            drops = format_drops(creature, db, Some(30), " <br> "),
+        );
+        file.write_all(line.as_bytes())?;
+    }
+    file.write_all(
+        r#"|-
+|}
+"#
+        .as_bytes(),
+    )?;
+
+    let out_path = Path::new("out/creature_infoboxes.txt");
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    for creature in &creatures {
+        let name = creature.name.as_ref().unwrap();
+        file.write_all(format!("\n\n ===  {}  ===  \n\n", name).as_bytes())?;
+        file.write_all(format_creature_infobox(creature, db).as_bytes())?;
     }
+    Ok(())
+}
+
+pub(crate) fn dump_jobs(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/jobs.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    file.write_all(
+        r#"{| class="wikitable" style="width: 60%; font-size: 90%;"
+! style="width: 20%" | Job
+! style="width: 30%" | Starting skills
+! style="width: 50%" | Starting items
+"#
+        .as_bytes(),
+    )?;
+    for job in &db.jobs {
+        file.write_all(format_job_line(job, db).as_bytes())?;
+    }
+    file.write_all(
+        r#"|-
+|}
+"#
+        .as_bytes(),
+    )?;
+    Ok(())
+}
+
+fn format_job_line(job: &Job, db: &Db) -> String {
+    let name = job.name.clone().unwrap_or_else(|| job.id.clone());
+    let skills = job
+        .skills
+        .iter()
+        .map(|(id, min, max)| {
+            let range = if min == max {
+                min.to_string()
+            } else {
+                format!("{}-{}", min, max)
+            };
+            format!("{} {}", db.localization.skill_name(id), range)
+        })
+        .collect::<Vec<_>>()
+        .join(" <br> ");
+    let items = job
+        .items
+        .iter()
+        .filter_map(|(id, cnt)| linkify_named_item(db, id, *cnt, Some(30)))
+        .collect::<Vec<_>>()
+        .join(" <br> ");
+    format!(
+        r#"|-
+| align="center" | [[{name}]]
+| align="left-index" | {skills}
+| align="left-index" | {items}
+"#,
+        name = name,
+        skills = skills,
+        items = items,
+    )
+}
+
+pub(crate) fn dump_talents(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/talents.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    for tree in &db.talent_trees {
+        let job_name = db
+            .jobs
+            .iter()
+            .find(|j| j.id == tree.job)
+            .and_then(|j| j.name.clone())
+            .unwrap_or_else(|| tree.job.clone());
+        file.write_all(format!("\n\n ===  {}  ===  \n\n", job_name).as_bytes())?;
+        for subtree in &tree.subtrees {
+            file.write_all(format_talent_subtree(subtree, db).as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn format_talent_subtree(subtree: &TalentSubTree, db: &Db) -> String {
+    let mut result = format!(
+        r#"{{| class="wikitable" style="width: 70%; font-size: 90%;"
+|+ {name}
+! style="width: 5%" | Tier
+! style="width: 15%" | Talent
+! style="width: 40%" | Description
+! style="width: 20%" | Stats
+! style="width: 20%" | Unlocks recipes
+"#,
+        name = subtree.name.as_deref().unwrap_or(&subtree.id)
+    );
+    for (tier, option) in subtree.options.iter().enumerate() {
+        for talent_id in option {
+            let talent = match db.talents.iter().find(|t| &t.id == talent_id) {
+                Some(t) => t,
+                None => {
+                    log::warn!(
+                        "talent tree {} refers to unknown talent {}",
+                        subtree.id,
+                        talent_id
+                    );
+                    continue;
+                }
+            };
+            let stats = talent
+                .stats
+                .iter()
+                .map(|(stat, value)| format!("{} {}", stat, value))
+                .collect::<Vec<_>>()
+                .join(" <br> ");
+            let recipes = talent
+                .unlocked_recipes
+                .iter()
+                .filter_map(|id| linkify_named_item(db, id, 1, Some(30)))
+                .collect::<Vec<_>>()
+                .join(" <br> ");
+            result += &format!(
+                r#"|-
+| align="center" | {tier}
+| align="center" | [[{name}]]
+| align="left-index" | {description}
+| align="left-index" | {stats}
+| align="left-index" | {recipes}
+"#,
+                tier = tier + 1,
+                name = talent.name.as_deref().unwrap_or(&talent.id),
+                description = talent.description.as_deref().unwrap_or(""),
+                stats = stats,
+                recipes = recipes,
+            );
+        }
+    }
+    result += "|-\n|}\n";
+    result
+}
+
+pub(crate) fn dump_missions(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/missions.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    file.write_all(
+        r#"{| class="wikitable sortable" style="width: 80%; font-size: 90%;"
+! style="width: 15%" | Mission
+! style="width: 10%" | Type
+! style="width: 10%" | Reward
+! style="width: 15%" | Reputation
+! style="width: 15%" | Locations
+! style="width: 20%" | Items
+! style="width: 15%" | Monsters
+"#
+        .as_bytes(),
+    )?;
+
+    let mut missions = db.missions.iter().collect::<Vec<_>>();
+    missions.sort_by(|a, b| b.reward.cmp(&a.reward).then_with(|| a.id.cmp(&b.id)));
+    for mission in missions {
+        let name = match mission.name.as_ref() {
+            Some(n) => n,
+            None => continue,
+        };
+        let reputation = mission
+            .reputation
+            .iter()
+            .map(|(faction, amount)| format!("{} {:+}", faction, amount))
+            .collect::<Vec<_>>()
+            .join(" <br> ");
+        let locations = mission
+            .location_types
+            .iter()
+            .map(|(from, to)| format!("{} → {}", from, to))
+            .collect::<Vec<_>>()
+            .join(" <br> ");
+        let items = mission
+            .required_items
+            .iter()
+            .filter_map(|id| linkify_named_item(db, id, 1, Some(30)))
+            .collect::<Vec<_>>()
+            .join(" <br> ");
+        let monsters = mission
+            .monsters
+            .iter()
+            .map(|(species, amount)| {
+                let name = db
+                    .creatures
+                    .iter()
+                    .find(|c| &c.id == species)
+                    .and_then(|c| c.name.clone())
+                    .unwrap_or_else(|| species.clone());
+                if *amount > 1 {
+                    format!("[[{}]] (x{})", name, amount)
+                } else {
+                    format!("[[{}]]", name)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" <br> ");
+        let line = format!(
+            r#"|-
+| align="center" | {name}
+| align="center" | {kind}
+| align="center" | {reward}
+| align="left-index" | {reputation}
+| align="left-index" | {locations}
+| align="left-index" | {items}
+| align="left-index" | {monsters}
+"#,
+            name = match mission.description.as_ref() {
+                Some(d) => format!("<abbr title=\"{}\">{}</abbr>", d.replace('"', "'"), name),
+                None => name.clone(),
+            },
+            kind = mission.kind,
+            reward = mission.reward,
+            reputation = reputation,
+            locations = locations,
+            items = items,
+            monsters = monsters,
+        );
+        file.write_all(line.as_bytes())?;
+    }
+    file.write_all(
+        r#"|-
+|}
+"#
+        .as_bytes(),
+    )?;
+    Ok(())
+}
+
+/// What every store sells, and at what price.
+pub(crate) fn dump_stores(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/stores.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+
+    let items = db
+        .items_by_name()
+        .filter(|it| it.name.is_some())
+        .collect::<Vec<_>>();
+    for loc in db.store_location_types() {
+        let name = loc.name.as_deref().unwrap_or(&loc.id);
+        file.write_all(format!("\n\n ===  {}  ===  \n\n", name).as_bytes())?;
+        file.write_all(
+            r#"{| class="wikitable sortable" style="width: 40%; font-size: 90%;"
+! style="width: 50%" | Item
+! style="width: 25%" | Price
+! style="width: 25%" | Multiplier
+"#
+            .as_bytes(),
+        )?;
+        for item in &items {
+            let (mult, is_sold) = item.prices.at(&loc.id);
+            if !is_sold {
+                continue;
+            }
+            let line = format!(
+                r#"|-
+| align="left-index" | {item}
+| align="center" | {price}
+| align="center" | {mult}
+"#,
+                item = linkify_item(db, &item.id, 1, Some(30))?,
+                price = item.prices.price_at(&loc.id).round(),
+                mult = mult,
+            );
+            file.write_all(line.as_bytes())?;
+        }
+        file.write_all(
+            r#"|-
+|}
+"#
+            .as_bytes(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Every level resource, per biome, with its commonness there.
+pub(crate) fn dump_biome_resources(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/biome_resources.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+
+    let items = db
+        .items_by_name()
+        .filter(|it| it.name.is_some() && it.level_resource.is_some())
+        .collect::<Vec<_>>();
+    for biome in &db.biomes {
+        let mut rows = items
+            .iter()
+            .filter_map(|it| {
+                let com = db.biome_commonness(it.level_resource.as_ref().unwrap(), &biome.id)?;
+                Some((it, com))
+            })
+            .filter(|(_, com)| *com > 0.0)
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            continue;
+        }
+        // most common first
+        rows.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        let name = biome.name.as_deref().unwrap_or(&biome.id);
+        file.write_all(format!("\n\n ===  {}  ===  \n\n", name).as_bytes())?;
+        file.write_all(
+            r#"{| class="wikitable sortable" style="width: 30%; font-size: 90%;"
+! style="width: 70%" | Resource
+! style="width: 30%" | Commonness
+"#
+            .as_bytes(),
+        )?;
+        for (item, com) in rows {
+            let line = format!(
+                r#"|-
+| align="left-index" | {item}
+| align="center" | {com}
+"#,
+                item = linkify_item(db, &item.id, 1, Some(30))?,
+                com = (com * 100.0).round(),
+            );
+            file.write_all(line.as_bytes())?;
+        }
+        file.write_all(
+            r#"|-
+|}
+"#
+            .as_bytes(),
+        )?;
+    }
+    Ok(())
+}
+
+pub(crate) fn dump_submarines(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/submarines.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    file.write_all(
+        r#"{| class="wikitable sortable" style="width: 70%; font-size: 90%;"
+! style="width: 15%" | Submarine
+! style="width: 10%" | Class
+! style="width: 10%" | Tier
+! style="width: 10%" | Price
+! style="width: 10%" | Crew
+! style="width: 15%" | Dimensions
+! style="width: 10%" | Items
+! style="width: 20%" | <abbr title="Total base price of all installed items">Item value</abbr>
+"#
+        .as_bytes(),
+    )?;
+    let or_dash = |x: Option<String>| x.unwrap_or_else(|| "-".into());
+    for sub in &db.submarines {
+        let (item_count, item_value) = sub.items.iter().fold((0, 0), |(cnt, value), (id, n)| {
+            let price = match db.item(id) {
+                Some(it) => it.prices.base_price,
+                None => 0,
+            };
+            (cnt + n, value + price * n)
+        });
+        let line = format!(
+            r#"|-
+| align="center" | [[{name}]]
+| align="center" | {class}
+| align="center" | {tier}
+| align="center" | {price}
+| align="center" | {crew}
+| align="center" | {dimensions}
+| align="center" | {item_count}
+| align="center" | {item_value}
+"#,
+            name = sub.name,
+            class = or_dash(sub.class.clone()),
+            tier = or_dash(sub.tier.map(|t| t.to_string())),
+            price = or_dash(sub.price.map(|p| p.to_string())),
+            crew = or_dash(sub.crew_size.map(|(min, max)| format!("{}-{}", min, max))),
+            dimensions = or_dash(sub.dimensions.map(|(w, h)| format!("{} x {}", w, h))),
+            item_count = item_count,
+            item_value = item_value,
+        );
+        file.write_all(line.as_bytes())?;
+    }
+    file.write_all(
+        r#"|-
+|}
+"#
+        .as_bytes(),
+    )?;
+
+    let out_path = Path::new("out/submarine_items.txt");
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    for sub in &db.submarines {
+        file.write_all(format!("\n\n ===  {}  ===  \n\n", sub.name).as_bytes())?;
+        if let Some(description) = sub.description.as_ref() {
+            file.write_all(format!("''{}''\n\n", description).as_bytes())?;
+        }
+        for (id, cnt) in &sub.items {
+            // structural things like hulls and gaps have no price and hence are not in the db
+            let link = match linkify_named_item(db, id, *cnt, Some(30)) {
+                Some(x) => x,
+                None => continue,
+            };
+            let line = format!("* {}\n", link);
+            file.write_all(line.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Upgrade prices for every level, per submarine class since classes can have different max levels.
+pub(crate) fn dump_upgrades(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/upgrades.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+
+    let mut classes = db
+        .submarines
+        .iter()
+        .filter_map(|s| s.class.as_ref().map(|c| c.to_lowercase()))
+        .collect::<Vec<_>>();
+    classes.sort();
+    classes.dedup();
+    let classes = if classes.is_empty() {
+        vec![None]
+    } else {
+        classes.into_iter().map(Some).collect()
+    };
+
+    for class in &classes {
+        let title = class.as_deref().unwrap_or("All submarines");
+        file.write_all(format!("\n\n ===  {}  ===  \n\n", title).as_bytes())?;
+        file.write_all(
+            r#"{| class="wikitable sortable" style="width: 60%; font-size: 90%;"
+! style="width: 20%" | Upgrade
+! style="width: 15%" | Category
+! style="width: 20%" | Effect
+! style="width: 10%" | Max level
+! style="width: 20%" | Price per level
+! style="width: 15%" | Total
+"#
+            .as_bytes(),
+        )?;
+        for module in &db.upgrades {
+            let max_level = module.max_level_for(class.as_deref());
+            let prices = (1..=max_level)
+                .map(|level| module.price.at_level(level, max_level))
+                .collect::<Vec<_>>();
+            let categories = module
+                .categories
+                .iter()
+                .map(|id| {
+                    db.upgrade_categories
+                        .iter()
+                        .find(|c| &c.id == id)
+                        .and_then(|c| c.name.clone())
+                        .unwrap_or_else(|| id.clone())
+                })
+                .collect::<Vec<_>>()
+                .join(" <br> ");
+            let effects = module
+                .properties
+                .iter()
+                .map(|(target, prop, change)| format!("{} {}: {}", target, prop, change))
+                .collect::<Vec<_>>()
+                .join(" <br> ");
+            let line = format!(
+                r#"|-
+| align="center" | {name}
+| align="center" | {categories}
+| align="left-index" | {effects}
+| align="center" | {max_level}
+| align="left-index" | {prices}
+| align="center" | {total}
+"#,
+                name = module.name.as_deref().unwrap_or(&module.id),
+                categories = categories,
+                effects = effects,
+                max_level = max_level,
+                prices = prices
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| format!("{}: {}", i + 1, p))
+                    .collect::<Vec<_>>()
+                    .join(" <br> "),
+                total = prices.iter().sum::<i32>(),
+            );
+            file.write_all(line.as_bytes())?;
+        }
+        file.write_all(
+            r#"|-
+|}
+"#
+            .as_bytes(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Outpost generation configs, and the location types each of them is used in.
+pub(crate) fn dump_outposts(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/outposts.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    file.write_all(
+        r#"{| class="wikitable sortable" style="width: 50%; font-size: 90%;"
+! style="width: 25%" | Outpost
+! style="width: 35%" | Locations
+! style="width: 40%" | Modules
+"#
+        .as_bytes(),
+    )?;
+    for outpost in &db.outposts {
+        let locations = if outpost.location_types.is_empty() {
+            "any".to_string()
+        } else {
+            outpost
+                .location_types
+                .iter()
+                .map(|lt| db.location_type_name(lt))
+                .collect::<Vec<_>>()
+                .join(" <br> ")
+        };
+        let modules = outpost
+            .module_counts
+            .iter()
+            .map(|(flag, cnt)| format!("{} (x{})", flag, cnt))
+            .collect::<Vec<_>>()
+            .join(" <br> ");
+        let line = format!(
+            r#"|-
+| align="center" | {id}
+| align="left-index" | {locations}
+| align="left-index" | {modules}
+"#,
+            id = outpost.id,
+            locations = locations,
+            modules = modules,
+        );
+        file.write_all(line.as_bytes())?;
+    }
+    file.write_all(
+        r#"|-
+|}
+"#
+        .as_bytes(),
+    )?;
+    Ok(())
+}
+
+fn format_event_items(db: &Db, items: &[RequiredItem]) -> String {
+    items
+        .iter()
+        .map(|mat| match mat {
+            RequiredItem::Id(id) => {
+                linkify_named_item(db, id, 1, Some(30)).unwrap_or_else(|| id.clone())
+            }
+            RequiredItem::Tag(tag) => format!("any ''{}''", tag),
+        })
+        .collect::<Vec<_>>()
+        .join(" <br> ")
+}
+
+/// Events that involve items, with the location types they can happen in.
+pub(crate) fn dump_events(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/events.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    file.write_all(
+        r#"{| class="wikitable sortable" style="width: 70%; font-size: 90%;"
+! style="width: 25%" | Event
+! style="width: 25%" | Locations
+! style="width: 25%" | Gives
+! style="width: 25%" | Requires
+"#
+        .as_bytes(),
+    )?;
+    let mut events = db
+        .events
+        .iter()
+        .filter(|e| !e.gives.is_empty() || !e.requires.is_empty())
+        .collect::<Vec<_>>();
+    events.sort_by(|a, b| a.id.cmp(&b.id));
+    events.dedup_by(|a, b| a.id == b.id);
+    for event in events {
+        let locations = match db.event_location_types(&event.id) {
+            None => "any".to_string(),
+            // not a part of any set, so it never happens on its own
+            Some(lts) if lts.is_empty() => continue,
+            Some(lts) => lts
+                .iter()
+                .map(|lt| db.location_type_name(lt))
+                .collect::<Vec<_>>()
+                .join(" <br> "),
+        };
+        let line = format!(
+            r#"|-
+| align="center" | {id}
+| align="left-index" | {locations}
+| align="left-index" | {gives}
+| align="left-index" | {requires}
+"#,
+            id = event.id,
+            locations = locations,
+            gives = format_event_items(db, &event.gives),
+            requires = format_event_items(db, &event.requires),
+        );
+        file.write_all(line.as_bytes())?;
+    }
+    file.write_all(
+        r#"|-
+|}
+"#
+        .as_bytes(),
+    )?;
+    Ok(())
+}
+
+/// Item identifiers defined more than once, and which of the definitions is used.
+pub(crate) fn dump_duplicate_items(db: &Db) -> std::io::Result<()> {
+    let out_path = Path::new("out/duplicate_items.txt");
+    std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(out_path)
+        .unwrap();
+    for dup in db.duplicate_items.iter().filter(|d| db.is_selected(&d.id)) {
+        let line = format!(
+            "{}: using {}{}, ignoring {}\n",
+            dup.id,
+            dup.kept.display(),
+            if dup.overridden { " (override)" } else { "" },
+            dup.ignored
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        file.write_all(line.as_bytes())?;
+    }
+    Ok(())
 }
@@ -0,0 +1,315 @@
+//! Item filter expressions, e.g. `tag:ore and price>100 and not has:fabricate`.
+//!
+//! A filter is made of terms combined with `and`, `or`, `not` and parentheses;
+//! `and` binds tighter than `or`. The terms are:
+//!
+//! * `id:steel`, `tag:ore`, `fabricator:medicalfabricator` and `sold:outpost` match exactly
+//! * `name:bar` matches a part of the localized name, ignoring case
+//! * `has:name`, `has:fabricate`, `has:deconstruct` and `has:level_resource`
+//!   match items that have the part at all
+//! * `price>100` compares the base price, with any of `<`, `<=`, `>`, `>=`, `=` and `!=`
+//!
+//! Values with spaces or operators go in double quotes, e.g. `name:"steel bar"`.
+
+use crate::Item;
+
+use std::str::FromStr;
+
+/// Part of an item that `has:` checks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    /// localized name
+    Name,
+    /// fabrication recipe
+    Fabricate,
+    /// deconstruction
+    Deconstruct,
+    /// level resource data
+    LevelResource,
+}
+
+/// Comparison operator of a numeric term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+    /// `=`
+    Equal,
+    /// `!=`
+    NotEqual,
+}
+impl Comparison {
+    fn apply(&self, a: f32, b: f32) -> bool {
+        match self {
+            Comparison::Less => a < b,
+            Comparison::LessOrEqual => a <= b,
+            Comparison::Greater => a > b,
+            Comparison::GreaterOrEqual => a >= b,
+            Comparison::Equal => a == b,
+            Comparison::NotEqual => a != b,
+        }
+    }
+}
+
+/// A parsed filter expression, see the module docs for the syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// both match
+    And(Box<Filter>, Box<Filter>),
+    /// either matches
+    Or(Box<Filter>, Box<Filter>),
+    /// doesn't match
+    Not(Box<Filter>),
+    /// `id:<identifier>`
+    Id(String),
+    /// `tag:<tag>`
+    Tag(String),
+    /// `name:<text>`, lowercased
+    Name(String),
+    /// `fabricator:<fabricator type>`
+    Fabricator(String),
+    /// `sold:<location type>`
+    SoldAt(String),
+    /// `has:<part>`
+    Has(Part),
+    /// `price<op><number>`, on the base price
+    Price(Comparison, f32),
+}
+impl Filter {
+    /// Whether the item matches the filter.
+    pub fn matches(&self, item: &Item) -> bool {
+        match self {
+            Filter::And(a, b) => a.matches(item) && b.matches(item),
+            Filter::Or(a, b) => a.matches(item) || b.matches(item),
+            Filter::Not(f) => !f.matches(item),
+            Filter::Id(id) => &item.id == id,
+            Filter::Tag(tag) => item.tags.contains(tag),
+            Filter::Name(text) => item
+                .name
+                .as_deref()
+                .is_some_and(|name| name.to_lowercase().contains(text)),
+            Filter::Fabricator(fabricator) => item
+                .fabricate
+                .as_ref()
+                .is_some_and(|fab| &fab.fabricator == fabricator),
+            Filter::SoldAt(location) => item.prices.at(location).1,
+            Filter::Has(part) => match part {
+                Part::Name => item.name.is_some(),
+                Part::Fabricate => item.fabricate.is_some(),
+                Part::Deconstruct => item.deconstruct.is_some(),
+                Part::LevelResource => item.level_resource.is_some(),
+            },
+            Filter::Price(cmp, value) => cmp.apply(item.prices.base_price as f32, *value),
+        }
+    }
+}
+
+/// Why a filter expression could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError(pub String);
+impl std::fmt::Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid filter: {}", self.0)
+    }
+}
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Colon,
+    Op(Comparison),
+    Open,
+    Close,
+}
+
+// for error messages
+fn describe(token: &Option<Token>) -> String {
+    match token {
+        None => "the end".into(),
+        Some(Token::Word(w)) => format!("{:?}", w),
+        Some(Token::Colon) => "\":\"".into(),
+        Some(Token::Op(_)) => "a comparison".into(),
+        Some(Token::Open) => "\"(\"".into(),
+        Some(Token::Close) => "\")\"".into(),
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ':' => Token::Colon,
+            '"' => {
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(FilterError("unterminated quote".into())),
+                    }
+                }
+                Token::Word(word)
+            }
+            '<' | '>' | '=' | '!' => {
+                let or_equal = chars.next_if_eq(&'=').is_some();
+                Token::Op(match (c, or_equal) {
+                    ('<', false) => Comparison::Less,
+                    ('<', true) => Comparison::LessOrEqual,
+                    ('>', false) => Comparison::Greater,
+                    ('>', true) => Comparison::GreaterOrEqual,
+                    ('=', _) => Comparison::Equal,
+                    ('!', true) => Comparison::NotEqual,
+                    _ => return Err(FilterError("expected \"=\" after \"!\"".into())),
+                })
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"():<>=!\"".contains(*c))
+                {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek() == Some(&Token::Word(keyword.into())) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<String, FilterError> {
+        match self.next() {
+            Some(Token::Word(w)) => Ok(w),
+            other => Err(FilterError(format!(
+                "expected {}, found {}",
+                what,
+                describe(&other)
+            ))),
+        }
+    }
+
+    fn or(&mut self) -> Result<Filter, FilterError> {
+        let mut result = self.and()?;
+        while self.eat_keyword("or") {
+            result = Filter::Or(Box::new(result), Box::new(self.and()?));
+        }
+        Ok(result)
+    }
+
+    fn and(&mut self) -> Result<Filter, FilterError> {
+        let mut result = self.not()?;
+        while self.eat_keyword("and") {
+            result = Filter::And(Box::new(result), Box::new(self.not()?));
+        }
+        Ok(result)
+    }
+
+    fn not(&mut self) -> Result<Filter, FilterError> {
+        if self.eat_keyword("not") {
+            return Ok(Filter::Not(Box::new(self.not()?)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let result = self.or()?;
+            return match self.next() {
+                Some(Token::Close) => Ok(result),
+                other => Err(FilterError(format!(
+                    "expected \")\", found {}",
+                    describe(&other)
+                ))),
+            };
+        }
+        self.term()
+    }
+
+    fn term(&mut self) -> Result<Filter, FilterError> {
+        let key = self.word("a term")?;
+        match self.next() {
+            Some(Token::Colon) => {
+                let value = self.word(&format!("a value after {}:", key))?;
+                Ok(match key.as_str() {
+                    "id" => Filter::Id(value),
+                    "tag" => Filter::Tag(value),
+                    "name" => Filter::Name(value.to_lowercase()),
+                    "fabricator" => Filter::Fabricator(value),
+                    "sold" => Filter::SoldAt(value),
+                    "has" => Filter::Has(match value.as_str() {
+                        "name" => Part::Name,
+                        "fabricate" => Part::Fabricate,
+                        "deconstruct" => Part::Deconstruct,
+                        "level_resource" => Part::LevelResource,
+                        _ => return Err(FilterError(format!("unknown part {:?}", value))),
+                    }),
+                    _ => return Err(FilterError(format!("unknown term {:?}", key))),
+                })
+            }
+            Some(Token::Op(cmp)) => {
+                let value = self.word(&format!("a number after {}", key))?;
+                let value = value
+                    .parse()
+                    .map_err(|_| FilterError(format!("{:?} is not a number", value)))?;
+                match key.as_str() {
+                    "price" => Ok(Filter::Price(cmp, value)),
+                    _ => Err(FilterError(format!("unknown number {:?}", key))),
+                }
+            }
+            other => Err(FilterError(format!(
+                "expected \":\" or a comparison after {}, found {}",
+                key,
+                describe(&other)
+            ))),
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Filter, FilterError> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let result = parser.or()?;
+        match parser.next() {
+            None => Ok(result),
+            other => Err(FilterError(format!("unexpected {}", describe(&other)))),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Recipes as a graph of items.
+
+use crate::{Item, RequiredItem};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What an edge of the recipe graph stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EdgeKind {
+    /// material -> fabricated item
+    Fabricate,
+    /// deconstructed item -> material
+    Deconstruct,
+}
+impl EdgeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            EdgeKind::Fabricate => "fabricate",
+            EdgeKind::Deconstruct => "deconstruct",
+        }
+    }
+}
+
+/// An edge of the recipe graph.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edge {
+    /// id of the consumed item
+    pub from: String,
+    /// id of the produced item
+    pub to: String,
+    /// fabrication or deconstruction
+    pub kind: EdgeKind,
+    /// how many of the material are consumed or produced
+    pub amount: i32,
+}
+
+/// Material flow between items. Edges always point from what is consumed to what is produced.
+#[derive(Debug, Clone)]
+pub struct RecipeGraph {
+    /// id -> display name
+    pub nodes: BTreeMap<String, String>,
+    /// edges, sorted
+    pub edges: BTreeSet<Edge>,
+}
+
+impl RecipeGraph {
+    /// Builds the graph of all the recipes of the items.
+    pub fn build(items: &[Item]) -> RecipeGraph {
+        let mut graph = RecipeGraph {
+            nodes: BTreeMap::new(),
+            edges: BTreeSet::new(),
+        };
+        for item in items {
+            graph
+                .nodes
+                .entry(item.id.clone())
+                .or_insert_with(|| item.name.clone().unwrap_or_else(|| item.id.clone()));
+        }
+        for item in items {
+            if let Some(fab) = item.fabricate.as_ref() {
+                for (mat, cnt) in &fab.mats {
+                    // a tag can be satisfied by any of the items having it, so every one of them gets an edge
+                    let mat_ids = match mat {
+                        RequiredItem::Id(id) => vec![id.as_str()],
+                        RequiredItem::Tag(tag) => items
+                            .iter()
+                            .filter(|it| it.tags.contains(tag))
+                            .map(|it| it.id.as_str())
+                            .collect(),
+                    };
+                    for mat_id in mat_ids {
+                        graph.add_edge(mat_id, &item.id, EdgeKind::Fabricate, *cnt);
+                    }
+                }
+            }
+            if let Some(decon) = item.deconstruct.as_ref() {
+                for (mat_id, cnt) in &decon.mats {
+                    graph.add_edge(&item.id, mat_id, EdgeKind::Deconstruct, *cnt);
+                }
+            }
+        }
+        graph
+    }
+
+    fn add_edge(&mut self, from: &str, to: &str, kind: EdgeKind, amount: i32) {
+        for id in &[from, to] {
+            // materials without a price are not in the item list, but should still show up
+            self.nodes
+                .entry(id.to_string())
+                .or_insert_with(|| id.to_string());
+        }
+        self.edges.insert(Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            kind,
+            amount,
+        });
+    }
+
+    /// Everything that can be made out of `root`, by any combination of fabricating and deconstructing.
+    /// Empty if `root` isn't in the graph.
+    pub fn subtree(&self, root: &str) -> RecipeGraph {
+        let mut reachable = BTreeSet::new();
+        let mut queue = vec![root.to_string()];
+        while let Some(id) = queue.pop() {
+            if !reachable.insert(id.clone()) {
+                continue;
+            }
+            for edge in self.edges.iter().filter(|e| e.from == id) {
+                queue.push(edge.to.clone());
+            }
+        }
+        RecipeGraph {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|(id, _)| reachable.contains(*id))
+                .map(|(id, name)| (id.clone(), name.clone()))
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|e| reachable.contains(&e.from) && reachable.contains(&e.to))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Renders the graph in Graphviz format.
+    pub fn to_dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut result = String::new();
+        result += "digraph recipes {\n";
+        for (id, name) in &self.nodes {
+            result += &format!("    \"{}\" [label=\"{}\"];\n", escape(id), escape(name));
+        }
+        for edge in &self.edges {
+            let style = match edge.kind {
+                EdgeKind::Fabricate => "solid",
+                EdgeKind::Deconstruct => "dashed",
+            };
+            result += &format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\", style={}];\n",
+                escape(&edge.from),
+                escape(&edge.to),
+                edge.amount,
+                style
+            );
+        }
+        result += "}\n";
+        result
+    }
+
+    /// Renders the graph in GraphML format.
+    pub fn to_graphml(&self) -> String {
+        let escape = |s: &str| {
+            s.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        };
+        let mut result = String::new();
+        result += r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="name" for="node" attr.name="name" attr.type="string"/>
+  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>
+  <key id="amount" for="edge" attr.name="amount" attr.type="int"/>
+  <graph id="recipes" edgedefault="directed">
+"#;
+        for (id, name) in &self.nodes {
+            result += &format!(
+                "    <node id=\"{}\"><data key=\"name\">{}</data></node>\n",
+                escape(id),
+                escape(name)
+            );
+        }
+        for edge in &self.edges {
+            result += &format!(
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"kind\">{}</data><data key=\"amount\">{}</data></edge>\n",
+                escape(&edge.from),
+                escape(&edge.to),
+                edge.kind.name(),
+                edge.amount
+            );
+        }
+        result += "  </graph>\n</graphml>\n";
+        result
+    }
+}
@@ -0,0 +1,765 @@
+//! Parser and data model for the Barotrauma game files.
+//!
+//! [`parse_db`] reads everything the wiki tables need from a game install into a [`Db`].
+//! The [`cost`], [`graph`] and [`loops`] modules do the analysis on top of the parsed items,
+//! [`lint`] checks the item files for mistakes, and [`filter`] selects which items the dumps include.
+
+#![warn(missing_docs)]
+
+mod cache;
+pub mod cost;
+pub mod filter;
+pub mod graph;
+pub mod lint;
+pub mod loops;
+mod parse;
+
+pub use cache::{parse_db_cached, CACHE_FORMAT};
+pub use parse::parse_db;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// An item prefab, merged from its XML definition and the localization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Item {
+    /// identifier, as used by recipes and other references
+    pub id: String,
+    /// localized name, None for items that are not meant to be shown to players
+    pub name: Option<String>,
+    /// tags recipes can refer to instead of an identifier
+    pub tags: Vec<String>,
+    /// store prices
+    pub prices: Prices,
+    /// recipe, if the item can be fabricated
+    pub fabricate: Option<Fabricate>,
+    /// what the item breaks down into, if it can be deconstructed
+    pub deconstruct: Option<Deconstruct>,
+    /// whether the item has an `<InventoryIcon>`
+    pub has_inventory_icon: bool,
+    /// whether the item has a `<Sprite>`
+    pub has_sprite: bool,
+    /// set for items that spawn in levels, such as minerals
+    pub level_resource: Option<LevelResource>,
+    /// ids of the items whose recipes this item unlocks
+    pub unlocks_recipes: Vec<String>,
+}
+
+/// An item identifier that is defined more than once, and which definition is used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateItem {
+    /// item identifier
+    pub id: String,
+    /// file of the definition in use, relative to the game directory
+    pub kept: PathBuf,
+    /// files of the other definitions, in parse order
+    pub ignored: Vec<PathBuf>,
+    /// whether the kept definition is an `<Override>`, which wins wherever it is;
+    /// otherwise the first one was kept
+    pub overridden: bool,
+}
+
+/// How often an item spawns as a level resource.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelResource {
+    /// commonness in level types without an explicit entry
+    pub comonness_default: f32,
+    /// level generation params id -> commonness
+    pub comonness: HashMap<String, f32>,
+}
+
+/// Store prices of an item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prices {
+    /// price before the location multiplier
+    pub base_price: i32,
+    /// location -> (multiplier, is_sold)
+    pub locations: BTreeMap<String, (f32, bool)>,
+}
+impl Prices {
+    /// (multiplier, is_sold) at the location type.
+    /// Items with no `<Price>` entry for a location are not sold there.
+    pub fn at(&self, location: &str) -> (f32, bool) {
+        self.locations
+            .get(location)
+            .copied()
+            .unwrap_or((1.0, false))
+    }
+    /// Price at the location type, with the multiplier applied.
+    pub fn price_at(&self, location: &str) -> f32 {
+        self.base_price as f32 * self.at(location).0
+    }
+}
+
+/// An item's fabrication recipe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fabricate {
+    /// how many items one fabrication produces
+    pub out_amount: i32,
+    /// skill id -> required level
+    pub skills: Vec<(String, i32)>,
+    /// fabrication time, in seconds
+    pub time: i32,
+    /// material -> amount
+    pub mats: Vec<(RequiredItem, i32)>,
+    /// fabricator type, e.g. "fabricator" or "medicalfabricator"
+    pub fabricator: String,
+    /// can only be fabricated once unlocked, see `unlocked_by`
+    pub requires_recipe: bool,
+    /// talents and items that unlock the recipe
+    pub unlocked_by: Vec<RecipeUnlock>,
+}
+
+/// Something that unlocks a recipe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecipeUnlock {
+    /// talent id
+    Talent(String),
+    /// id of an item, usually a blueprint
+    Item(String),
+}
+
+/// A recipe material, which is either a specific item or any item with a tag.
+/// Ids sort before tags.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RequiredItem {
+    /// item id
+    Id(String),
+    /// item tag
+    Tag(String),
+}
+
+/// What an item breaks down into.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deconstruct {
+    /// deconstruction time, in seconds
+    pub time: i32,
+    /// material id -> amount
+    pub mats: Vec<(String, i32)>,
+}
+
+/// A character prefab: a monster, or a human.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Creature {
+    /// species name, lowercased
+    pub id: String,
+    /// localized name
+    pub name: Option<String>,
+    /// crew members and other characters with jobs and names
+    pub has_info: bool,
+    /// max vitality
+    pub health: Option<f32>,
+    /// limbs from the ragdoll, with their attacks
+    pub limbs: Vec<Limb>,
+    /// what the AI reacts to
+    pub ai_targets: Vec<AiTarget>,
+    /// animation type -> movement speed
+    pub speeds: BTreeMap<String, f32>,
+    /// items it carries, and hence drops
+    pub inventory: Vec<(String, i32)>,
+}
+
+/// A ragdoll limb.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Limb {
+    /// limb name, e.g. "Head"
+    pub name: String,
+    /// the limb's attack, if any
+    pub attack: Option<Attack>,
+}
+
+/// A limb attack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attack {
+    /// attack range, in game units
+    pub range: f32,
+    /// damage to walls
+    pub structure_damage: f32,
+    /// affliction id -> strength
+    pub afflictions: Vec<(String, f32)>,
+}
+
+/// How a creature's AI reacts to targets with a tag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiTarget {
+    /// target tag, e.g. "human"
+    pub tag: String,
+    /// AI state, e.g. "attack" or "avoid"
+    pub state: String,
+    /// higher is more important
+    pub priority: f32,
+}
+
+/// A crew job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    /// job identifier
+    pub id: String,
+    /// localized name
+    pub name: Option<String>,
+    /// skill id -> (min, max) starting level
+    pub skills: Vec<(String, i32, i32)>,
+    /// starting item id -> amount
+    pub items: Vec<(String, i32)>,
+}
+
+/// The talents available to a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TalentTree {
+    /// job identifier
+    pub job: String,
+    /// branches of the tree
+    pub subtrees: Vec<TalentSubTree>,
+}
+
+/// A branch of a talent tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TalentSubTree {
+    /// subtree identifier
+    pub id: String,
+    /// localized name
+    pub name: Option<String>,
+    /// one entry per tier, each with the talents to pick from
+    pub options: Vec<Vec<String>>,
+}
+
+/// A talent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Talent {
+    /// talent identifier
+    pub id: String,
+    /// localized name
+    pub name: Option<String>,
+    /// localized description
+    pub description: Option<String>,
+    /// ids of the items this talent allows to fabricate
+    pub unlocked_recipes: Vec<String>,
+    /// stat type -> value
+    pub stats: Vec<(String, f32)>,
+}
+
+/// A mission prefab.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mission {
+    /// mission identifier
+    pub id: String,
+    /// e.g. "salvage" for `<SalvageMission>`
+    pub kind: String,
+    /// localized name
+    pub name: Option<String>,
+    /// localized description
+    pub description: Option<String>,
+    /// money reward
+    pub reward: i32,
+    /// faction -> reputation change
+    pub reputation: Vec<(String, f32)>,
+    /// (from, to) location types the mission is available between
+    pub location_types: Vec<(String, String)>,
+    /// ids of the items the mission is about
+    pub required_items: Vec<String>,
+    /// species -> amount
+    pub monsters: Vec<(String, i32)>,
+}
+
+/// A location type, e.g. outpost or city.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocationType {
+    /// location type identifier
+    pub id: String,
+    /// localized name
+    pub name: Option<String>,
+    /// fraction of the price the store pays for items it buys from the player
+    pub sell_price_modifier: f32,
+}
+impl LocationType {
+    /// The game's sell price modifier for location types that don't set one.
+    pub const DEFAULT_SELL_PRICE_MODIFIER: f32 = 0.3;
+}
+
+/// A biome of the campaign map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Biome {
+    /// biome identifier
+    pub id: String,
+    /// localized name
+    pub name: Option<String>,
+}
+
+/// A level type. `LevelResource` commonness is keyed by these, rather than by biome.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelGenerationParams {
+    /// level generation params identifier
+    pub id: String,
+    /// biomes the level can be generated in, or "any"
+    pub biomes: Vec<String>,
+}
+
+/// A submarine from the vanilla `.sub` files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Submarine {
+    /// submarine name
+    pub name: String,
+    /// description text
+    pub description: Option<String>,
+    /// e.g. "scout" or "transport"
+    pub class: Option<String>,
+    /// submarine tier
+    pub tier: Option<i32>,
+    /// purchase price
+    pub price: Option<i32>,
+    /// recommended (min, max)
+    pub crew_size: Option<(i32, i32)>,
+    /// (width, height), in game units
+    pub dimensions: Option<(f32, f32)>,
+    /// installed item id -> count
+    pub items: Vec<(String, i32)>,
+}
+
+/// A group of submarine upgrades.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradeCategory {
+    /// category identifier
+    pub id: String,
+    /// localized name
+    pub name: Option<String>,
+}
+
+/// A submarine upgrade.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradeModule {
+    /// upgrade identifier
+    pub id: String,
+    /// localized name
+    pub name: Option<String>,
+    /// category ids
+    pub categories: Vec<String>,
+    /// max level, unless overridden for the submarine class
+    pub max_level: i32,
+    /// submarine class -> max level, if it differs from the default
+    pub class_max_levels: Vec<(String, i32)>,
+    /// price of each level
+    pub price: UpgradePrice,
+    /// (target, property, change), e.g. ("structure", "maxhealth", "+25%")
+    pub properties: Vec<(String, String, String)>,
+}
+impl UpgradeModule {
+    /// Max level for submarines of the class, or the default one if `class` is None.
+    pub fn max_level_for(&self, class: Option<&str>) -> i32 {
+        self.class_max_levels
+            .iter()
+            .find(|(c, _)| Some(c.as_str()) == class)
+            .map(|(_, level)| *level)
+            .unwrap_or(self.max_level)
+    }
+}
+
+/// How much the levels of an upgrade cost.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradePrice {
+    /// price before the per-level increase
+    pub base_price: i32,
+    /// percentage the price goes up by, at the first level
+    pub increase_low: f32,
+    /// percentage the price goes up by, at the last level
+    pub increase_high: f32,
+}
+impl UpgradePrice {
+    /// Price of buying the given level, counting from 1.
+    /// Same as the game, which interpolates the increase by the level the submarine is at before
+    /// buying over the max level: the first level is `increase_low` more expensive,
+    /// and the last one doesn't quite reach `increase_high`.
+    pub fn at_level(&self, level: i32, max_level: i32) -> i32 {
+        let t = if max_level > 0 {
+            (level - 1) as f32 / max_level as f32
+        } else {
+            0.0
+        };
+        let increase = self.increase_low + (self.increase_high - self.increase_low) * t;
+        (self.base_price as f32 * (1.0 + increase / 100.0)).round() as i32
+    }
+}
+
+/// An outpost generation config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutpostParams {
+    /// config identifier
+    pub id: String,
+    /// empty if allowed anywhere
+    pub location_types: Vec<String>,
+    /// module flag -> how many such modules the outpost has
+    pub module_counts: Vec<(String, i32)>,
+}
+
+/// A random event prefab.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    /// event identifier
+    pub id: String,
+    /// items spawned for the crew
+    pub gives: Vec<RequiredItem>,
+    /// items checked for or taken away
+    pub requires: Vec<RequiredItem>,
+}
+
+/// A set of random events that can happen together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSet {
+    /// inherited from the parent set if not given; empty if allowed anywhere
+    pub location_types: Vec<String>,
+    /// ids of the events in the set
+    pub events: Vec<String>,
+}
+
+/// English texts, keyed by their lowercased tag name, e.g. "entityname.wrench".
+#[derive(Serialize, Deserialize)]
+pub struct Localization {
+    /// text key -> text
+    pub entries: HashMap<String, String>,
+}
+impl Localization {
+    /// Localized item name, if any.
+    pub fn item_name_opt(&self, id: &str) -> Option<&str> {
+        self.entries
+            .get(&format!("entityname.{}", id))
+            .map(|s| s.as_str())
+    }
+    /// Localized species name, if any.
+    pub fn character_name_opt(&self, species: &str) -> Option<&str> {
+        self.entries
+            .get(&format!("character.{}", species))
+            .map(|s| s.as_str())
+    }
+    /// Localized job name, if any.
+    pub fn job_name_opt(&self, id: &str) -> Option<&str> {
+        self.entries
+            .get(&format!("jobname.{}", id))
+            .map(|s| s.as_str())
+    }
+    /// Localized skill name, falling back to the id.
+    pub fn skill_name(&self, id: &str) -> String {
+        match self.entries.get(&format!("skillname.{}", id)) {
+            Some(name) => name.clone(),
+            None => {
+                log::warn!("no localized name for skill {}", id);
+                id.to_string()
+            }
+        }
+    }
+    /// Text with the given key, if any.
+    pub fn get_opt(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(|s| s.as_str())
+    }
+    /// Localized affliction name, falling back to the id.
+    pub fn affliction_name(&self, id: &str) -> String {
+        self.entries
+            .get(&format!("afflictionname.{}", id))
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    }
+    /// Localized item description, if any.
+    pub fn item_description(&self, id: &str) -> Option<&str> {
+        self.entries
+            .get(&format!("entitydescription.{}", id))
+            .map(|s| s.as_str())
+    }
+}
+
+/// Reverse recipe lookup: what a material is used for and where it comes from.
+#[derive(Default)]
+pub struct Usages {
+    /// material -> (item whose recipe needs it, amount needed)
+    pub used_in: HashMap<RequiredItem, Vec<(String, i32)>>,
+    /// material -> (item that deconstructs into it, amount produced)
+    pub obtained_from: HashMap<String, Vec<(String, i32)>>,
+}
+impl Usages {
+    pub(crate) fn build(items: &[Item]) -> Usages {
+        let mut usages = Usages {
+            used_in: HashMap::new(),
+            obtained_from: HashMap::new(),
+        };
+        for item in items {
+            if let Some(fab) = item.fabricate.as_ref() {
+                for (mat, cnt) in &fab.mats {
+                    usages
+                        .used_in
+                        .entry(mat.clone())
+                        .or_default()
+                        .push((item.id.clone(), *cnt));
+                }
+            }
+            if let Some(decon) = item.deconstruct.as_ref() {
+                for (mat_id, cnt) in &decon.mats {
+                    usages
+                        .obtained_from
+                        .entry(mat_id.clone())
+                        .or_default()
+                        .push((item.id.clone(), *cnt));
+                }
+            }
+        }
+        usages
+    }
+}
+
+/// Lookup tables into [`Db::items`], by position.
+/// Every item is included; the selection is only applied by the accessors that list rows.
+#[derive(Default)]
+pub struct ItemIndex {
+    /// identifier -> item
+    pub by_id: HashMap<String, usize>,
+    /// per item, whether it matches the filter given to [`Db::select`]
+    pub selected: Vec<bool>,
+    /// tag -> items that have it, in file order
+    pub by_tag: HashMap<String, Vec<usize>>,
+    /// fabricator type -> items made in it, in file order
+    pub by_fabricator: HashMap<String, Vec<usize>>,
+    /// items sorted by name, unnamed ones first
+    pub by_name: Vec<usize>,
+}
+impl ItemIndex {
+    pub(crate) fn build(items: &[Item], filter: Option<&filter::Filter>) -> ItemIndex {
+        let mut index = ItemIndex::default();
+        for (i, item) in items.iter().enumerate() {
+            index.by_id.insert(item.id.clone(), i);
+            index.selected.push(filter.is_none_or(|f| f.matches(item)));
+            for tag in &item.tags {
+                index.by_tag.entry(tag.clone()).or_default().push(i);
+            }
+            if let Some(fab) = item.fabricate.as_ref() {
+                index
+                    .by_fabricator
+                    .entry(fab.fabricator.clone())
+                    .or_default()
+                    .push(i);
+            }
+        }
+        index.by_name = (0..items.len()).collect();
+        index.by_name.sort_by_key(|&i| &items[i].name);
+        index
+    }
+}
+
+/// An item identifier no item is defined with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownItem(pub String);
+impl std::fmt::Display for UnknownItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unknown item {:?}", self.0)
+    }
+}
+impl std::error::Error for UnknownItem {}
+impl From<UnknownItem> for std::io::Error {
+    fn from(e: UnknownItem) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::NotFound, e)
+    }
+}
+
+/// Everything parsed from a game install, see [`parse_db`].
+#[derive(Serialize, Deserialize)]
+pub struct Db {
+    /// game version, from `Barotrauma.deps.json`
+    pub version: String,
+    /// item prefabs, in file order, one per identifier
+    pub items: Vec<Item>,
+    /// identifiers that are defined more than once
+    pub duplicate_items: Vec<DuplicateItem>,
+    /// character prefabs
+    pub creatures: Vec<Creature>,
+    /// crew jobs
+    pub jobs: Vec<Job>,
+    /// talent trees, one per job
+    pub talent_trees: Vec<TalentTree>,
+    /// talents from all the trees
+    pub talents: Vec<Talent>,
+    /// mission prefabs
+    pub missions: Vec<Mission>,
+    /// location types of the campaign map
+    pub location_types: Vec<LocationType>,
+    /// biomes of the campaign map
+    pub biomes: Vec<Biome>,
+    /// level types
+    pub level_params: Vec<LevelGenerationParams>,
+    /// vanilla submarines
+    pub submarines: Vec<Submarine>,
+    /// submarine upgrade categories
+    pub upgrade_categories: Vec<UpgradeCategory>,
+    /// submarine upgrades
+    pub upgrades: Vec<UpgradeModule>,
+    /// outpost generation configs
+    pub outposts: Vec<OutpostParams>,
+    /// random event prefabs
+    pub events: Vec<Event>,
+    /// random event sets, nested ones flattened
+    pub event_sets: Vec<EventSet>,
+    /// English texts
+    pub localization: Localization,
+    /// reverse recipe lookup, built from `items`
+    // not cached, since json can't have tags as keys; rebuilt on load instead
+    #[serde(skip)]
+    pub usages: Usages,
+    /// item lookup tables, built from `items`
+    #[serde(skip)]
+    pub index: ItemIndex,
+}
+impl Db {
+    /// Item with this identifier.
+    pub fn item(&self, id: &str) -> Option<&Item> {
+        self.index.by_id.get(id).map(|&i| &self.items[i])
+    }
+    /// Item with this identifier, or an error saying it doesn't exist.
+    pub fn try_item(&self, id: &str) -> Result<&Item, UnknownItem> {
+        self.item(id).ok_or_else(|| UnknownItem(id.to_string()))
+    }
+    /// Whether the item exists and has a name, i.e. a wiki page to link to.
+    pub fn has_named_item(&self, id: &str) -> bool {
+        self.item(id).is_some_and(|it| it.name.is_some())
+    }
+    /// Restricts the item lists below, and hence the dumps, to the items matching the filter.
+    /// Lookups by identifier and by tag still find every item.
+    pub fn select(&mut self, filter: &filter::Filter) {
+        self.index = ItemIndex::build(&self.items, Some(filter));
+    }
+    /// Whether the item exists and is selected.
+    pub fn is_selected(&self, id: &str) -> bool {
+        self.index
+            .by_id
+            .get(id)
+            .is_some_and(|&i| self.index.selected[i])
+    }
+    /// Selected items, in file order.
+    pub fn selected_items(&self) -> impl Iterator<Item = &Item> {
+        self.items
+            .iter()
+            .zip(&self.index.selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(item, _)| item)
+    }
+    /// Items with this tag, in file order, whether selected or not:
+    /// a recipe taking any of them reads the same with or without a filter.
+    pub fn items_with_tag<'a>(&'a self, tag: &str) -> impl Iterator<Item = &'a Item> + 'a {
+        self.indexed(self.index.by_tag.get(tag).into_iter().flatten(), false)
+    }
+    /// Selected items made in this fabricator type, in file order.
+    pub fn items_made_in<'a>(&'a self, fabricator: &str) -> impl Iterator<Item = &'a Item> + 'a {
+        self.indexed(
+            self.index
+                .by_fabricator
+                .get(fabricator)
+                .into_iter()
+                .flatten(),
+            true,
+        )
+    }
+    /// Selected items sorted by name, unnamed ones first.
+    pub fn items_by_name(&self) -> impl Iterator<Item = &Item> {
+        self.indexed(self.index.by_name.iter(), true)
+    }
+    fn indexed<'a>(
+        &'a self,
+        positions: impl Iterator<Item = &'a usize> + 'a,
+        only_selected: bool,
+    ) -> impl Iterator<Item = &'a Item> + 'a {
+        positions
+            .filter(move |&&i| !only_selected || self.index.selected[i])
+            .map(move |&i| &self.items[i])
+    }
+    /// Recipes that need this item, either by identifier or by one of its tags.
+    pub fn used_in(&self, id: &str) -> Vec<(&str, i32)> {
+        let mut keys = vec![RequiredItem::Id(id.to_string())];
+        if let Some(item) = self.item(id) {
+            keys.extend(item.tags.iter().map(|t| RequiredItem::Tag(t.clone())));
+        }
+        let mut result = keys
+            .iter()
+            .filter_map(|k| self.usages.used_in.get(k))
+            .flatten()
+            .map(|(product, cnt)| (product.as_str(), *cnt))
+            .collect::<Vec<_>>();
+        result.sort();
+        result.dedup();
+        result
+    }
+    /// Localized talent name, falling back to the id.
+    pub fn talent_name<'a>(&'a self, id: &'a str) -> &'a str {
+        self.talents
+            .iter()
+            .find(|t| t.id == id)
+            .and_then(|t| t.name.as_deref())
+            .unwrap_or(id)
+    }
+    /// Location types that have a store, i.e. appear in some item's prices.
+    pub fn store_location_types(&self) -> Vec<&LocationType> {
+        self.location_types
+            .iter()
+            .filter(|lt| {
+                self.items
+                    .iter()
+                    .any(|it| it.prices.locations.contains_key(&lt.id))
+            })
+            .collect()
+    }
+    /// How common a level resource is in the given biome.
+    /// Entries for level types of the biome win over ones for level types allowed anywhere,
+    /// and if several level types have one, the highest is used, whatever the file order.
+    /// None if no level can be generated in the biome.
+    pub fn biome_commonness(&self, lr: &LevelResource, biome: &str) -> Option<f32> {
+        let in_biome = |lp: &LevelGenerationParams| lp.biomes.iter().any(|b| b == biome);
+        let anywhere = |lp: &LevelGenerationParams| lp.biomes.iter().any(|b| b == "any");
+        // biomes no level can be generated in don't have resources either
+        if !self
+            .level_params
+            .iter()
+            .any(|lp| in_biome(lp) || anywhere(lp))
+        {
+            return None;
+        }
+        let highest = |applies: &dyn Fn(&LevelGenerationParams) -> bool| {
+            self.level_params
+                .iter()
+                .filter(|lp| applies(lp))
+                .filter_map(|lp| lr.comonness.get(&lp.id).copied())
+                .reduce(f32::max)
+        };
+        Some(
+            highest(&in_biome)
+                .or_else(|| highest(&anywhere))
+                .unwrap_or(lr.comonness_default),
+        )
+    }
+    /// Localized location type name, falling back to the id.
+    pub fn location_type_name<'a>(&'a self, id: &'a str) -> &'a str {
+        self.location_types
+            .iter()
+            .find(|lt| lt.id == id)
+            .and_then(|lt| lt.name.as_deref())
+            .unwrap_or(id)
+    }
+    /// Location types the event can happen in, None if it isn't restricted.
+    pub fn event_location_types(&self, event_id: &str) -> Option<Vec<&str>> {
+        let mut result = vec![];
+        for set in self
+            .event_sets
+            .iter()
+            .filter(|s| s.events.iter().any(|e| e == event_id))
+        {
+            if set.location_types.is_empty() {
+                return None;
+            }
+            result.extend(set.location_types.iter().map(|x| x.as_str()));
+        }
+        result.sort();
+        result.dedup();
+        Some(result)
+    }
+    /// Items that deconstruct into this item.
+    pub fn obtained_from(&self, id: &str) -> Vec<(&str, i32)> {
+        self.usages
+            .obtained_from
+            .get(id)
+            .map(|v| v.iter().map(|(src, cnt)| (src.as_str(), *cnt)).collect())
+            .unwrap_or_default()
+    }
+}
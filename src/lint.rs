@@ -0,0 +1,417 @@
+//! Consistency checks of the item files, for mod authors.
+
+use crate::parse::{
+    content_root, files_with_extension, item_elems, item_files, item_name, localization_path,
+    parse_localization, DECONSTRUCT_ATTRS, DECONSTRUCT_ITEM_ATTRS, FABRICATE_ATTRS,
+    FABRICATE_ITEM_ATTRS, LOCATION_PRICE_ATTRS, PRICE_ATTRS, UPGRADE_PRICE_ATTRS,
+};
+
+use serde::Serialize;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// How bad an issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// the content is broken, e.g. a recipe needs an item that doesn't exist
+    Error,
+    /// likely a mistake, or something the parser doesn't understand
+    Warning,
+    /// possibly intended, e.g. a tag only the game code checks for
+    Info,
+}
+impl Severity {
+    /// Lowercase name, same as in the json output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+/// A problem found by [`lint`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    /// how bad it is
+    pub severity: Severity,
+    /// short machine-readable name of the check, e.g. "dangling-reference"
+    pub check: &'static str,
+    /// file the issue is in
+    pub file: PathBuf,
+    /// 1-based line
+    pub line: u32,
+    /// 1-based column
+    pub column: u32,
+    /// human-readable description
+    pub message: String,
+}
+
+struct Linter<'a, 'input> {
+    file: &'a Path,
+    doc: &'a roxmltree::Document<'input>,
+    issues: &'a mut Vec<Issue>,
+}
+
+impl<'a, 'input> Linter<'a, 'input> {
+    fn report(&mut self, pos: usize, severity: Severity, check: &'static str, message: String) {
+        let pos = self.doc.text_pos_at(pos);
+        self.issues.push(Issue {
+            severity,
+            check,
+            file: self.file.to_owned(),
+            line: pos.row,
+            column: pos.col,
+            message,
+        });
+    }
+
+    // attribute names are compared exactly, since the item parser doesn't ignore case either
+    fn check_attributes(&mut self, elem: roxmltree::Node, known: &[&str]) {
+        self.check_attributes_by(elem, known, |a, b| a == b);
+    }
+
+    // for the parts the parser reads ignoring case
+    fn check_attributes_ci(&mut self, elem: roxmltree::Node, known: &[&str]) {
+        self.check_attributes_by(elem, known, |a, b| a.eq_ignore_ascii_case(b));
+    }
+
+    fn check_attributes_by(
+        &mut self,
+        elem: roxmltree::Node,
+        known: &[&str],
+        eq: impl Fn(&str, &str) -> bool,
+    ) {
+        for attr in elem.attributes() {
+            if !known.iter().any(|k| eq(attr.name(), k)) {
+                self.report(
+                    attr.range().start,
+                    Severity::Warning,
+                    "unknown-attribute",
+                    format!(
+                        "unknown attribute {:?} on <{}>",
+                        attr.name(),
+                        elem.tag_name().name()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn children<'a, 'input: 'a>(
+    elem: roxmltree::Node<'a, 'input>,
+    names: &'a [&str],
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> + 'a {
+    elem.children()
+        .filter(move |x| names.contains(&x.tag_name().name()))
+}
+
+fn item_tags<'a>(item_elem: roxmltree::Node<'a, '_>) -> impl Iterator<Item = &'a str> {
+    item_elem
+        .attribute("Tags")
+        .unwrap_or("")
+        .split(',')
+        .filter(|x| !x.is_empty())
+}
+
+// every comma separated value of every attribute in the content files, except for the item tags themselves
+fn referenced_values(game_path: &Path) -> BTreeSet<String> {
+    let mut result = BTreeSet::new();
+    let content_path = game_path.join("Content");
+    for path in crate::parse::files_with_extension(&content_path, "xml", true) {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let doc = match roxmltree::Document::parse(&content) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        for elem in doc.descendants().filter(|x| x.is_element()) {
+            for attr in elem.attributes() {
+                if attr.name() == "Tags" {
+                    continue;
+                }
+                result.extend(attr.value().split(',').map(|x| x.trim().to_string()));
+            }
+        }
+    }
+    result
+}
+
+// ids and tags of the items of the game a mod is linted against
+fn base_items(base_game: &Path) -> (BTreeSet<String>, BTreeSet<String>) {
+    let mut ids = BTreeSet::new();
+    let mut tags = BTreeSet::new();
+    for path in item_files(base_game) {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let doc = match roxmltree::Document::parse(&content) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        for (item_elem, _) in item_elems(&doc) {
+            ids.extend(item_elem.attribute("identifier").map(|x| x.to_string()));
+            tags.extend(item_tags(item_elem).map(|x| x.to_string()));
+        }
+    }
+    (ids, tags)
+}
+
+/// Checks the item files of a game or mod install for
+/// dangling item references in recipes, duplicate identifiers, missing names, unknown attributes,
+/// prices without any location and tags nothing refers to,
+/// and the upgrade prices for unknown attributes.
+/// Missing names are only reported when there is an English localization,
+/// which mods usually don't have.
+/// When linting a mod, `base_game` is the game install its references to vanilla items resolve against.
+/// Issues are sorted by file and position.
+pub fn lint(game_path: &Path, base_game: Option<&Path>) -> Vec<Issue> {
+    let localization = if localization_path(game_path).is_file() {
+        Some(parse_localization(game_path))
+    } else {
+        None
+    };
+
+    let mut issues = vec![];
+    let mut files = vec![];
+    for path in item_files(game_path) {
+        match std::fs::read_to_string(&path) {
+            Ok(content) => files.push((path, content)),
+            Err(e) => issues.push(Issue {
+                severity: Severity::Error,
+                check: "unreadable-file",
+                file: path,
+                line: 1,
+                column: 1,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    let mut docs = vec![];
+    for (path, content) in &files {
+        match roxmltree::Document::parse(content) {
+            Ok(doc) => docs.push((path.as_path(), doc)),
+            Err(e) => {
+                let pos = e.pos();
+                issues.push(Issue {
+                    severity: Severity::Error,
+                    check: "invalid-xml",
+                    file: path.clone(),
+                    line: pos.row,
+                    column: pos.col,
+                    message: e.to_string(),
+                });
+            }
+        }
+    }
+
+    // id -> where it is defined, and whether it's an override
+    let mut definitions: BTreeMap<&str, Vec<(&Path, roxmltree::Node, bool)>> = BTreeMap::new();
+    let mut tags: BTreeSet<&str> = BTreeSet::new();
+    for (path, doc) in &docs {
+        for (item_elem, is_override) in item_elems(doc) {
+            if let Some(id) = item_elem.attribute("identifier") {
+                definitions
+                    .entry(id)
+                    .or_default()
+                    .push((path, item_elem, is_override));
+            }
+            tags.extend(item_tags(item_elem));
+        }
+    }
+    let mut referenced = referenced_values(game_path);
+    let (base_ids, base_tags) = match base_game {
+        Some(base_game) => {
+            referenced.extend(referenced_values(base_game));
+            base_items(base_game)
+        }
+        None => Default::default(),
+    };
+    let known_item = |id: &str| definitions.contains_key(id) || base_ids.contains(id);
+    let known_tag = |tag: &str| tags.contains(tag) || base_tags.contains(tag);
+
+    for (path, doc) in &docs {
+        let mut linter = Linter {
+            file: path,
+            doc,
+            issues: &mut issues,
+        };
+        for (item_elem, is_override) in item_elems(doc) {
+            let pos = item_elem.range().start;
+            let id = match item_elem.attribute("identifier") {
+                Some(x) => x,
+                None => {
+                    linter.report(
+                        pos,
+                        Severity::Error,
+                        "missing-identifier",
+                        format!("<{}> without an identifier", item_elem.tag_name().name()),
+                    );
+                    continue;
+                }
+            };
+
+            // overrides are meant to replace the other definitions
+            let others = definitions[id]
+                .iter()
+                .filter(|(_, elem, _)| *elem != item_elem)
+                .filter(|(_, _, other_is_override)| !other_is_override)
+                .collect::<Vec<_>>();
+            if !is_override && !others.is_empty() {
+                let others = others
+                    .iter()
+                    .map(|(p, elem, _)| {
+                        let row = elem.document().text_pos_at(elem.range().start).row;
+                        format!("{}:{}", p.display(), row)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                linter.report(
+                    pos,
+                    Severity::Error,
+                    "duplicate-identifier",
+                    format!("{} is also defined at {}", id, others),
+                );
+            }
+
+            let price_elem = children(item_elem, &["Price"]).next();
+            let unnamed = localization
+                .as_ref()
+                .is_some_and(|loc| item_name(item_elem, id, loc).is_none());
+            if price_elem.is_some() && unnamed {
+                linter.report(
+                    pos,
+                    Severity::Warning,
+                    "missing-name",
+                    format!("{} has no name and no entityname.{} text", id, id),
+                );
+            }
+
+            if let Some(price_elem) = price_elem {
+                linter.check_attributes(price_elem, PRICE_ATTRS);
+                let locations = children(price_elem, &["Price"]).collect::<Vec<_>>();
+                if locations.is_empty() {
+                    linter.report(
+                        price_elem.range().start,
+                        Severity::Warning,
+                        "price-without-locations",
+                        format!("{} has a price, but no location it is sold at", id),
+                    );
+                }
+                for loc in locations {
+                    linter.check_attributes(loc, LOCATION_PRICE_ATTRS);
+                }
+            }
+
+            for fab in children(item_elem, &["Fabricate"]) {
+                linter.check_attributes(fab, FABRICATE_ATTRS);
+                for mat in children(fab, &["RequiredItem", "Item"]) {
+                    linter.check_attributes(mat, FABRICATE_ITEM_ATTRS);
+                    if let Some(mat_id) = mat.attribute("identifier") {
+                        if !known_item(mat_id) {
+                            linter.report(
+                                mat.range().start,
+                                Severity::Error,
+                                "dangling-reference",
+                                format!("recipe of {} needs unknown item {}", id, mat_id),
+                            );
+                        }
+                    } else if let Some(tag) = mat.attribute("tag") {
+                        if !known_tag(tag) {
+                            linter.report(
+                                mat.range().start,
+                                Severity::Error,
+                                "dangling-reference",
+                                format!("recipe of {} needs tag {} no item has", id, tag),
+                            );
+                        }
+                    } else {
+                        linter.report(
+                            mat.range().start,
+                            Severity::Error,
+                            "dangling-reference",
+                            format!("recipe of {} has a material without identifier or tag", id),
+                        );
+                    }
+                }
+            }
+
+            for decon in children(item_elem, &["Deconstruct"]) {
+                linter.check_attributes(decon, DECONSTRUCT_ATTRS);
+                for mat in children(decon, &["Item", "RequiredItem"]) {
+                    linter.check_attributes(mat, DECONSTRUCT_ITEM_ATTRS);
+                    let mat_id = mat.attribute("identifier").unwrap_or("");
+                    if !known_item(mat_id) {
+                        linter.report(
+                            mat.range().start,
+                            Severity::Error,
+                            "dangling-reference",
+                            format!("{} deconstructs into unknown item {:?}", id, mat_id),
+                        );
+                    }
+                }
+            }
+
+            if let Some(attr) = item_elem.attributes().iter().find(|a| a.name() == "Tags") {
+                for tag in item_tags(item_elem) {
+                    if !referenced.contains(tag) {
+                        linter.report(
+                            attr.range().start,
+                            Severity::Info,
+                            "unused-tag",
+                            format!("no content refers to tag {} of {}", tag, id),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    let upgrades_path = game_path.join("Content").join("UpgradeModules");
+    for path in files_with_extension(&upgrades_path, "xml", true) {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let doc = match roxmltree::Document::parse(&content) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let mut linter = Linter {
+            file: &path,
+            doc: &doc,
+            issues: &mut issues,
+        };
+        let root = match content_root(&doc) {
+            Some(x) => x,
+            None => continue,
+        };
+        for module in root
+            .children()
+            .filter(|x| x.tag_name().name().eq_ignore_ascii_case("upgrademodule"))
+        {
+            for price in module
+                .children()
+                .filter(|x| x.tag_name().name().eq_ignore_ascii_case("price"))
+            {
+                linter.check_attributes_ci(price, UPGRADE_PRICE_ATTRS);
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+    issues
+}
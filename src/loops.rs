@@ -0,0 +1,164 @@
+//! Detection of fabricate/deconstruct cycles that yield free materials.
+
+use crate::graph::{Edge, EdgeKind, RecipeGraph};
+use crate::{Item, RequiredItem};
+
+use std::collections::{BTreeMap, HashMap};
+
+// longer cycles are both unlikely to be exploitable in practice and expensive to enumerate
+const MAX_LOOP_LEN: usize = 6;
+const EPS: f64 = 1e-6;
+
+/// A sequence of fabrications and deconstructions that ends up with more materials than it started with.
+#[derive(Debug, Clone)]
+pub struct MaterialLoop {
+    /// what is done, to which item, how many times
+    pub steps: Vec<(EdgeKind, String, f64)>,
+    /// everything the steps consume, including what earlier steps produced
+    pub consumed: BTreeMap<String, f64>,
+    /// everything the steps produce, including what later steps consume
+    pub produced: BTreeMap<String, f64>,
+}
+impl MaterialLoop {
+    /// material -> amount, only for the materials that come out ahead
+    pub fn gain(&self) -> BTreeMap<String, f64> {
+        self.produced
+            .iter()
+            .map(|(id, amount)| (id.clone(), amount - self.consumed.get(id).unwrap_or(&0.0)))
+            .filter(|(_, amount)| *amount > EPS)
+            .collect()
+    }
+}
+
+// (consumed, produced) of doing the step once
+fn step_balance(
+    items: &HashMap<&str, &Item>,
+    edge: &Edge,
+) -> Option<(BTreeMap<String, f64>, BTreeMap<String, f64>)> {
+    let mut consumed = BTreeMap::new();
+    let mut produced = BTreeMap::new();
+    match edge.kind {
+        EdgeKind::Fabricate => {
+            let item = items.get(edge.to.as_str())?;
+            let fab = item.fabricate.as_ref()?;
+            for (mat, cnt) in &fab.mats {
+                let key = match mat {
+                    RequiredItem::Id(id) => id.clone(),
+                    // the tag is satisfied by whatever the previous step produced
+                    RequiredItem::Tag(tag)
+                        if items
+                            .get(edge.from.as_str())
+                            .map(|it| it.tags.contains(tag))
+                            .unwrap_or(false) =>
+                    {
+                        edge.from.clone()
+                    }
+                    RequiredItem::Tag(tag) => format!("[{}]", tag),
+                };
+                *consumed.entry(key).or_insert(0.0) += *cnt as f64;
+            }
+            produced.insert(item.id.clone(), fab.out_amount as f64);
+        }
+        EdgeKind::Deconstruct => {
+            let item = items.get(edge.from.as_str())?;
+            let decon = item.deconstruct.as_ref()?;
+            consumed.insert(item.id.clone(), 1.0);
+            for (mat_id, cnt) in &decon.mats {
+                *produced.entry(mat_id.clone()).or_insert(0.0) += *cnt as f64;
+            }
+        }
+    }
+    Some((consumed, produced))
+}
+
+fn evaluate(items: &HashMap<&str, &Item>, cycle: &[&Edge]) -> Option<MaterialLoop> {
+    let balances = cycle
+        .iter()
+        .map(|edge| step_balance(items, edge))
+        .collect::<Option<Vec<_>>>()?;
+
+    // repeat every step just enough to use up what the previous one produced
+    let mut times = vec![1.0];
+    for i in 1..cycle.len() {
+        let link = &cycle[i].from;
+        let made = balances[i - 1].1.get(link).copied().unwrap_or(0.0);
+        let needed = balances[i].0.get(link).copied().unwrap_or(0.0);
+        if made <= 0.0 || needed <= 0.0 {
+            return None;
+        }
+        times.push(times[i - 1] * made / needed);
+    }
+
+    let mut result = MaterialLoop {
+        steps: vec![],
+        consumed: BTreeMap::new(),
+        produced: BTreeMap::new(),
+    };
+    for ((edge, (consumed, produced)), k) in cycle.iter().zip(&balances).zip(&times) {
+        let item_id = match edge.kind {
+            EdgeKind::Fabricate => edge.to.clone(),
+            EdgeKind::Deconstruct => edge.from.clone(),
+        };
+        result.steps.push((edge.kind, item_id, *k));
+        for (id, amount) in consumed {
+            *result.consumed.entry(id.clone()).or_insert(0.0) += amount * k;
+        }
+        for (id, amount) in produced {
+            *result.produced.entry(id.clone()).or_insert(0.0) += amount * k;
+        }
+    }
+
+    let loses_something = result
+        .consumed
+        .iter()
+        .any(|(id, amount)| result.produced.get(id).unwrap_or(&0.0) - amount < -EPS);
+    if loses_something || result.gain().is_empty() {
+        return None;
+    }
+    Some(result)
+}
+
+/// Finds fabricate/deconstruct cycles which yield at least as much of every material as they consume,
+/// and strictly more of at least one.
+pub fn find_material_loops(items: &[Item]) -> Vec<MaterialLoop> {
+    let graph = RecipeGraph::build(items);
+    let by_id: HashMap<&str, &Item> = items.iter().rev().map(|it| (it.id.as_str(), it)).collect();
+    let mut outgoing: HashMap<&str, Vec<&Edge>> = HashMap::new();
+    for edge in &graph.edges {
+        outgoing.entry(edge.from.as_str()).or_default().push(edge);
+    }
+
+    let mut result = vec![];
+    // every cycle is only reported once: starting from its lexicographically smallest node
+    for start in graph.nodes.keys() {
+        let mut path: Vec<&Edge> = vec![];
+        let mut stack: Vec<(usize, &str)> = vec![(0, start.as_str())];
+        while let Some((idx, node)) = stack.pop() {
+            let edges = outgoing.get(node).map(|e| e.as_slice()).unwrap_or(&[]);
+            if idx >= edges.len() {
+                path.pop();
+                continue;
+            }
+            stack.push((idx + 1, node));
+            let edge = edges[idx];
+            if edge.to == *start {
+                let mut cycle = path.clone();
+                cycle.push(edge);
+                if let Some(l) = evaluate(&by_id, &cycle) {
+                    result.push(l);
+                }
+                continue;
+            }
+            if edge.to.as_str() < start.as_str()
+                || edge.to == node
+                || path.len() + 1 >= MAX_LOOP_LEN
+                || path.iter().any(|e| e.from == edge.to)
+            {
+                continue;
+            }
+            path.push(edge);
+            stack.push((0, edge.to.as_str()));
+        }
+    }
+    result
+}
@@ -1,104 +1,325 @@
 mod dump;
-mod parse;
-
-#[allow(unused_imports)]
-use log::{debug, info};
-
-use std::collections::{BTreeMap, HashMap};
-use std::path::Path;
-
-#[derive(Debug, Clone)]
-struct Item {
-    id: String,
-    name: Option<String>,
-    tags: Vec<String>,
-    prices: Prices,
-    fabricate: Option<Fabricate>,
-    deconstruct: Option<Deconstruct>,
-    has_inventory_icon: bool,
-    has_sprite: bool,
-    level_resource: Option<LevelResource>,
+mod query;
+
+use barostuff::filter::Filter;
+use barostuff::Db;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const CACHE_PATH: &str = "cache/db.json";
+const DEFAULT_GAME_PATH: &str = r"D:\games\SteamLibrary\steamapps\common\Barotrauma";
+
+type Dump = (&'static str, fn(&Db) -> std::io::Result<()>);
+
+// name -> dump, in the order they are run. infoboxes go last
+const DUMPS: &[Dump] = &[
+    ("prices", |db| {
+        dump::dump_prices(db);
+        Ok(())
+    }),
+    ("fabricate", |db| {
+        dump::dump_fabricate(db, "fabricator")?;
+        dump::dump_fabricate(db, "medicalfabricator")
+    }),
+    ("deconstruct", dump::dump_deconstruct),
+    ("crafting_costs", dump::dump_crafting_costs),
+    ("arbitrage", dump::dump_arbitrage),
+    ("recipe_graph", |db| dump::dump_recipe_graph(db, None)),
+    ("material_loops", dump::dump_material_loops),
+    ("usages", dump::dump_usages),
+    ("bestiary", dump::dump_bestiary),
+    ("jobs", dump::dump_jobs),
+    ("talents", dump::dump_talents),
+    ("missions", dump::dump_missions),
+    ("stores", dump::dump_stores),
+    ("biome_resources", dump::dump_biome_resources),
+    ("submarines", dump::dump_submarines),
+    ("upgrades", dump::dump_upgrades),
+    ("outposts", dump::dump_outposts),
+    ("events", dump::dump_events),
+    ("duplicate_items", dump::dump_duplicate_items),
+    ("infoboxes", dump::dump_infoboxes),
+];
+
+struct Args {
+    // None to just run the dumps once
+    command: Option<String>,
+    // `--game <path>` overrides the default install location
+    game_path: PathBuf,
+    // `--no-cache` always parses the game files again
+    no_cache: bool,
+    // `--dumps a,b` only runs those; all of them if not given
+    dumps: Option<Vec<String>>,
+    // `--json` prints lint issues and queried items as json instead of plain text
+    json: bool,
+    // identifier or name of the item to look up with `query`
+    item: Option<String>,
+    // `--filter <expr>` restricts the dumps to the matching items
+    filter: Option<Filter>,
+    // `--base-game <path>` is the game install a linted mod refers to
+    base_game: Option<PathBuf>,
+    // `--graph-root <id>` limits the recipe graph to what can be made out of that item
+    graph_root: Option<String>,
 }
 
-#[derive(Debug, Clone)]
-struct LevelResource{
-    comonness_default: f32,
-    comonness: HashMap<String, f32>,
+fn parse_args() -> Args {
+    let mut args = Args {
+        command: None,
+        game_path: PathBuf::from(DEFAULT_GAME_PATH),
+        no_cache: false,
+        dumps: None,
+        json: false,
+        item: None,
+        filter: None,
+        base_game: None,
+        graph_root: None,
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--game" => args.game_path = PathBuf::from(iter.next().expect("--game needs a path")),
+            "--no-cache" => args.no_cache = true,
+            "--json" => args.json = true,
+            "--base-game" => {
+                args.base_game = Some(PathBuf::from(
+                    iter.next().expect("--base-game needs a path"),
+                ))
+            }
+            "--graph-root" => {
+                args.graph_root = Some(iter.next().expect("--graph-root needs an item identifier"))
+            }
+            "--filter" => {
+                let expr = iter.next().expect("--filter needs an expression");
+                match expr.parse() {
+                    Ok(filter) => args.filter = Some(filter),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(2);
+                    }
+                }
+            }
+            "--dumps" => {
+                let names = iter.next().expect("--dumps needs a list of dumps");
+                let names = names
+                    .split(',')
+                    .map(|x| x.trim().to_string())
+                    .collect::<Vec<_>>();
+                for name in &names {
+                    if !DUMPS.iter().any(|(n, _)| n == name) {
+                        panic!(
+                            "unknown dump {:?}, expected one of {:?}",
+                            name,
+                            DUMPS.iter().map(|(n, _)| *n).collect::<Vec<_>>()
+                        );
+                    }
+                }
+                args.dumps = Some(names);
+            }
+            _ if args.command.is_none() && !arg.starts_with("--") => args.command = Some(arg),
+            _ if args.command.as_deref() == Some("query") && args.item.is_none() => {
+                args.item = Some(arg)
+            }
+            _ => panic!("unexpected argument {:?}", arg),
+        }
+    }
+    args
 }
 
-#[derive(Debug, Clone)]
-struct Prices {
-    base_price: i32,
-    // location -> (multiplier, is_sold)
-    locations: BTreeMap<String, (f32, bool)>,
+// fails if the cache can't be read or written
+fn load_db(args: &Args) -> std::io::Result<Db> {
+    let mut db = if args.no_cache {
+        barostuff::parse_db(&args.game_path)
+    } else {
+        barostuff::parse_db_cached(&args.game_path, Path::new(CACHE_PATH))?
+    };
+    if let Some(filter) = args.filter.as_ref() {
+        db.select(filter);
+    }
+    Ok(db)
 }
 
-#[derive(Debug, Clone)]
-struct Fabricate {
-    out_amount: i32,
-    skills: Vec<(String, i32)>,
-    time: i32,
-    mats: Vec<(RequiredItem, i32)>,
-    fabricator: String,
+// for the commands that run once
+fn load_db_or_exit(args: &Args) -> Db {
+    load_db(args).unwrap_or_else(|e| {
+        eprintln!("couldn't load the game data: {}", e);
+        std::process::exit(1);
+    })
 }
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord)]
-enum RequiredItem {
-    Id(String),
-    Tag(String),
+
+// false if some dump failed, e.g. because of a recipe referring to an unknown item
+fn run_dumps(db: &Db, args: &Args) -> bool {
+    let mut ok = true;
+    for (name, dump) in DUMPS {
+        if let Some(selected) = args.dumps.as_ref() {
+            if !selected.iter().any(|s| s == name) {
+                continue;
+            }
+        }
+        // the only dump with an option of its own
+        let result = match (*name, args.graph_root.as_deref()) {
+            ("recipe_graph", Some(root)) => dump::dump_recipe_graph(db, Some(root)),
+            _ => dump(db),
+        };
+        if let Err(e) = result {
+            log::error!("{} dump failed: {}", name, e);
+            ok = false;
+        }
+    }
+    ok
 }
-impl std::cmp::PartialOrd for RequiredItem {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(match (self, other) {
-            (RequiredItem::Id(a), RequiredItem::Id(b)) => a.cmp(b),
-            (RequiredItem::Tag(a), RequiredItem::Tag(b)) => a.cmp(b),
-            (RequiredItem::Id(_), RequiredItem::Tag(_)) => std::cmp::Ordering::Less,
-            (RequiredItem::Tag(_), RequiredItem::Id(_)) => std::cmp::Ordering::Greater,
+
+// out/ file name -> content
+fn read_outputs() -> BTreeMap<PathBuf, Vec<u8>> {
+    walkdir::WalkDir::new("out")
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| {
+            let content = std::fs::read(entry.path()).unwrap();
+            (entry.into_path(), content)
         })
+        .collect()
+}
+
+fn regenerate(args: &Args) {
+    let before = read_outputs();
+    // a half-saved file shouldn't end the session, so parse and cache errors are only reported
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let db = load_db(args)?;
+        Ok::<_, std::io::Error>(run_dumps(&db, args))
+    }));
+    match result {
+        Err(_) => {
+            println!("failed to regenerate, waiting for the next change");
+            return;
+        }
+        Ok(Err(e)) => {
+            println!(
+                "couldn't load the game data: {}, waiting for the next change",
+                e
+            );
+            return;
+        }
+        Ok(Ok(false)) => println!("some dumps failed, see above"),
+        Ok(Ok(true)) => {}
+    }
+    let after = read_outputs();
+    let changed = after
+        .iter()
+        .filter(|(path, content)| before.get(*path) != Some(content))
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    if changed.is_empty() {
+        println!("no outputs changed");
+    }
+    for path in changed {
+        println!("changed: {}", path.display());
     }
 }
 
-#[derive(Debug, Clone)]
-struct Deconstruct {
-    time: i32,
-    mats: Vec<(String, i32)>,
+fn is_content_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("xml") || ext.eq_ignore_ascii_case("sub"))
+        .unwrap_or(false)
 }
 
-struct Localization {
-    entries: HashMap<String, String>,
+fn watch(args: &Args) {
+    use notify::Watcher;
+
+    let content_path = args.game_path.join("Content");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).unwrap();
+    watcher
+        .watch(&content_path, notify::RecursiveMode::Recursive)
+        .unwrap();
+
+    regenerate(args);
+    loop {
+        println!("watching {} for changes", content_path.display());
+        loop {
+            let event: notify::Event = match rx.recv().unwrap() {
+                Ok(x) => x,
+                Err(e) => {
+                    log::warn!("watch error: {}", e);
+                    continue;
+                }
+            };
+            if !event.kind.is_access() && event.paths.iter().any(|p| is_content_file(p)) {
+                break;
+            }
+        }
+        // editors tend to write a file in several steps, so wait for them to finish
+        while rx
+            .recv_timeout(std::time::Duration::from_millis(300))
+            .is_ok()
+        {}
+        regenerate(args);
+    }
 }
-impl Localization {
-    fn item_name_opt(&self, id: &str) -> Option<&str> {
-        self.entries
-            .get(&format!("entityname.{}", id))
-            .map(|s| s.as_str())
+
+fn lint(args: &Args) {
+    let issues = barostuff::lint::lint(&args.game_path, args.base_game.as_deref());
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&issues).unwrap());
+    } else {
+        for issue in &issues {
+            println!(
+                "{}:{}:{}: {} [{}] {}",
+                issue.file.display(),
+                issue.line,
+                issue.column,
+                issue.severity.name(),
+                issue.check,
+                issue.message
+            );
+        }
     }
-    fn item_description(&self, id: &str) -> &str {
-        self.entries
-            .get(&format!("entitydescription.{}", id))
-            .unwrap()
-            .as_str()
+    if issues
+        .iter()
+        .any(|i| i.severity == barostuff::lint::Severity::Error)
+    {
+        std::process::exit(1);
     }
 }
 
-struct Db {
-    version: String,
-    items: Vec<Item>,
-    localization: Localization,
+fn query(args: &Args) {
+    let query = args
+        .item
+        .as_deref()
+        .expect("query needs an item identifier or name");
+    let db = load_db_or_exit(args);
+    let item = match query::find_item(&db, query) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let description = query::describe_item(&db, item);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&description).unwrap());
+    } else {
+        print!("{}", description.to_text());
+    }
 }
 
 fn stuff() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
 
-    let game_path = Path::new(r"D:\games\SteamLibrary\steamapps\common\Barotrauma");
-
-    let db = parse::parse_db(game_path);
-
-    dump::dump_prices(&db.items);
-    dump::dump_fabricate(&db.items, "fabricator").unwrap();
-    dump::dump_fabricate(&db.items, "medicalfabricator").unwrap();
-    dump::dump_deconstruct(&db.items).unwrap();
-
-    dump::dump_infoboxes(&db);
+    let args = parse_args();
+    match args.command.as_deref() {
+        None => {
+            let db = load_db_or_exit(&args);
+            if !run_dumps(&db, &args) {
+                std::process::exit(1);
+            }
+        }
+        Some("watch") => watch(&args),
+        Some("lint") => lint(&args),
+        Some("query") => query(&args),
+        Some(other) => panic!("unknown command {:?}", other),
+    }
 }
 
 fn main() {
@@ -1,16 +1,54 @@
-use crate::{Db, Deconstruct, Fabricate, Item, LevelResource, Localization, Prices, RequiredItem};
+use crate::{
+    AiTarget, Attack, Biome, Creature, Db, Deconstruct, DuplicateItem, Event, EventSet, Fabricate,
+    Item, ItemIndex, Job, LevelGenerationParams, LevelResource, Limb, Localization, LocationType,
+    Mission, OutpostParams, Prices, RecipeUnlock, RequiredItem, Submarine, Talent, TalentSubTree,
+    TalentTree, UpgradeCategory, UpgradeModule, UpgradePrice, Usages,
+};
 
-use std::collections::{BTreeMap, HashMap};
+use rayon::prelude::*;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
-pub(crate) fn parse_db(game_path: &Path) -> Db {
+/// Parses the game install at `game_path`, e.g. `steamapps/common/Barotrauma`.
+/// Panics on files it can not make sense of.
+pub fn parse_db(game_path: &Path) -> Db {
     let version = parse_version(game_path);
     let localization = parse_localization(game_path);
-    let items = parse_items(game_path, &localization);
+    let (mut items, duplicate_items) = parse_items(game_path, &localization);
+    let creatures = parse_creatures(game_path, &localization);
+    let jobs = parse_jobs(game_path, &localization);
+    let (talent_trees, talents) = parse_talents(game_path, &localization);
+    resolve_recipe_unlocks(&mut items, &talents);
+    let missions = parse_missions(game_path, &localization);
+    let (location_types, biomes, level_params) = parse_map(game_path, &localization);
+    let submarines = parse_submarines(game_path);
+    let (upgrade_categories, upgrades) = parse_upgrades(game_path, &localization);
+    let outposts = parse_outposts(game_path);
+    let (events, event_sets) = parse_events(game_path);
+    let usages = Usages::build(&items);
+    let index = ItemIndex::build(&items, None);
     Db {
         version,
         items,
+        duplicate_items,
+        creatures,
+        jobs,
+        talent_trees,
+        talents,
+        missions,
+        location_types,
+        biomes,
+        level_params,
+        submarines,
+        upgrade_categories,
+        upgrades,
+        outposts,
+        events,
+        event_sets,
         localization,
+        usages,
+        index,
     }
 }
 
@@ -18,19 +56,22 @@ pub(crate) fn parse_version(game_path: &Path) -> String {
     let path = game_path.join("Barotrauma.deps.json");
     let content = std::fs::read_to_string(path).unwrap();
     let re = regex::Regex::new(r#""Barotrauma/([^"]+)""#).unwrap();
-    for cap in re.captures_iter(&content) {
+    if let Some(cap) = re.captures_iter(&content).next() {
         return cap[1].to_string();
     }
     panic!("this should never happen - couldn't extract version");
 }
 
-fn parse_localization(game_path: &Path) -> Localization {
-    let loc_path = game_path
+pub(crate) fn localization_path(game_path: &Path) -> std::path::PathBuf {
+    game_path
         .join("Content")
         .join("Texts")
         .join("English")
-        .join("EnglishVanilla.xml");
-    let content = std::fs::read_to_string(loc_path).unwrap();
+        .join("EnglishVanilla.xml")
+}
+
+pub(crate) fn parse_localization(game_path: &Path) -> Localization {
+    let content = std::fs::read_to_string(localization_path(game_path)).unwrap();
     let doc = roxmltree::Document::parse(&content).unwrap();
     let root = doc.root();
 
@@ -67,27 +108,71 @@ fn parse_bool(s: &str) -> bool {
     }
 }
 
+// attributes the parser understands, or knows it can ignore; anything else is reported by the linter
+pub(crate) const PRICE_ATTRS: &[&str] = &[
+    "baseprice",
+    "soldeverywhere",
+    "canbespecial",
+    "minleveldifficulty",
+    "displaynonempty",
+];
+pub(crate) const LOCATION_PRICE_ATTRS: &[&str] = &[
+    "locationtype",
+    "multiplier",
+    "sold",
+    "minavailable",
+    "maxavailable",
+];
+pub(crate) const FABRICATE_ATTRS: &[&str] = &[
+    "suitablefabricators",
+    "requiredtime",
+    "amount",
+    "requiresrecipe",
+    "displayname",
+    "outcondition",
+    "fabricationlimitmin",
+    "fabricationlimitmax",
+    "quality",
+];
+pub(crate) const FABRICATE_ITEM_ATTRS: &[&str] = &[
+    "identifier",
+    "tag",
+    "mincondition",
+    "maxcondition",
+    "usecondition",
+    "description",
+];
+pub(crate) const UPGRADE_PRICE_ATTRS: &[&str] = &["baseprice", "increaselow", "increasehigh"];
+pub(crate) const DECONSTRUCT_ATTRS: &[&str] = &["time", "chooserandom"];
+pub(crate) const DECONSTRUCT_ITEM_ATTRS: &[&str] = &[
+    "identifier",
+    "mincondition",
+    "maxcondition",
+    "outcondition",
+    "copycondition",
+    "commonness",
+    "requiredotheritem",
+    "activatebuttontext",
+    "infotext",
+    "infotextonotheritemmissing",
+];
+
 fn parse_prices(elem: roxmltree::Node) -> Prices {
     let mut inner = BTreeMap::new();
     let base_price = elem.attribute("baseprice").unwrap().parse::<i32>().unwrap();
 
     // Note: e.g. wrench and diving knife are lacking both "soldeverywhere" and "sold", yet they are common
-    let is_sold_everywhere = elem.attribute("soldeverywhere").map(|x| parse_bool(x));
+    let is_sold_everywhere = elem.attribute("soldeverywhere").map(parse_bool);
 
     for child in elem.children().filter(|x| x.tag_name().name() == "Price") {
-        for attr in child.attributes() {
-            if !["locationtype", "multiplier", "sold", "minavailable"].contains(&attr.name()) {
-                println!("{:?}", attr);
-            }
-        }
         let has_min = child.attribute("minavailable").is_some();
-        let mut is_sold = child.attribute("sold").map(|x| parse_bool(x));
+        let mut is_sold = child.attribute("sold").map(parse_bool);
         if has_min || is_sold_everywhere == Some(true) {
             assert!(is_sold != Some(false));
             is_sold = Some(true);
         }
         // if neither of the "sold" "minavailable", "soldeverywhere" are present, then the item *is* sold: see, e.g. wrenches
-        if !has_min && is_sold_everywhere == None && is_sold == None {
+        if !has_min && is_sold_everywhere.is_none() && is_sold.is_none() {
             is_sold = Some(true);
         }
         let is_sold = is_sold.unwrap();
@@ -108,6 +193,15 @@ fn parse_prices(elem: roxmltree::Node) -> Prices {
     }
 }
 
+// none if the base price is missing, or any number is malformed
+fn parse_upgrade_price(elem: roxmltree::Node) -> Option<UpgradePrice> {
+    Some(UpgradePrice {
+        base_price: attr_ci(elem, "baseprice")?.parse().ok()?,
+        increase_low: attr_ci(elem, "increaselow").unwrap_or("0").parse().ok()?,
+        increase_high: attr_ci(elem, "increasehigh").unwrap_or("0").parse().ok()?,
+    })
+}
+
 // this is like O(n^2) or something but i don't care
 fn dedup_things<T: Clone + Eq>(things: &[T]) -> Vec<(T, i32)> {
     let mut dedupped: Vec<(T, i32)> = vec![];
@@ -166,6 +260,12 @@ fn parse_fabricate(elem: roxmltree::Node) -> Fabricate {
         skills,
         mats,
         fabricator: elem.attribute("suitablefabricators").unwrap().to_string(),
+        requires_recipe: elem
+            .attribute("requiresrecipe")
+            .map(|x| parse_bool(&x.to_lowercase()))
+            .unwrap_or(false),
+        // filled in by resolve_recipe_unlocks once talents are known
+        unlocked_by: vec![],
     }
 }
 
@@ -190,11 +290,7 @@ fn parse_level_resource(elem: roxmltree::Node) -> LevelResource {
         .children()
         .filter(|x| x.tag_name().name() == "Commonness")
     {
-        let com = item
-            .attribute("commonness")
-            .unwrap()
-            .parse()
-            .unwrap();
+        let com = item.attribute("commonness").unwrap().parse().unwrap();
         let leveltype = item.attribute("leveltype");
         match leveltype {
             Some(lt) => {
@@ -212,12 +308,120 @@ fn parse_level_resource(elem: roxmltree::Node) -> LevelResource {
     }
 }
 
-pub(crate) fn parse_items(game_path: impl AsRef<Path>, localization: &Localization) -> Vec<Item> {
-    let game_path = game_path.as_ref();
+// explicit name, then name identifier, then identifier
+pub(crate) fn item_name(
+    item_elem: roxmltree::Node,
+    id: &str,
+    localization: &Localization,
+) -> Option<String> {
+    let name = item_elem.attribute("name").filter(|x| !x.is_empty());
+    if let Some(name) = name {
+        return Some(name.to_string());
+    }
+    item_elem
+        .attribute("nameidentifier")
+        .and_then(|nid| localization.item_name_opt(nid))
+        .or_else(|| localization.item_name_opt(id))
+        .map(|x| x.to_string())
+}
+
+// item elements of a file, and whether they are in an <Override> block, i.e. replace an earlier definition.
+// the block can wrap the whole <Items>, some of the items in it, or be the root with the items right in it
+pub(crate) fn item_elems<'a, 'input>(
+    doc: &'a roxmltree::Document<'input>,
+) -> Vec<(roxmltree::Node<'a, 'input>, bool)> {
+    fn collect<'a, 'input>(
+        container: roxmltree::Node<'a, 'input>,
+        is_override: bool,
+        result: &mut Vec<(roxmltree::Node<'a, 'input>, bool)>,
+    ) {
+        for elem in container.children().filter(|x| x.is_element()) {
+            if elem.tag_name().name().eq_ignore_ascii_case("override") {
+                collect(elem, true, result);
+            } else if elem.tag_name().name().eq_ignore_ascii_case("items") {
+                collect(elem, is_override, result);
+            } else {
+                result.push((elem, is_override));
+            }
+        }
+    }
+    let mut result = vec![];
+    let root = doc.root_element();
+    if root.tag_name().name().eq_ignore_ascii_case("items") {
+        collect(root, false, &mut result);
+    } else if root.tag_name().name().eq_ignore_ascii_case("override") {
+        collect(root, true, &mut result);
+    }
+    result
+}
+
+// items of a single file, in file order, and whether they are overrides
+fn parse_item_file(path: &Path, localization: &Localization) -> Vec<(Item, bool)> {
+    let mut items = vec![];
+    // log::debug!("{}", path.display());
+    let content = std::fs::read_to_string(path).unwrap();
+    let doc = roxmltree::Document::parse(&content).unwrap();
+    for (item_elem, is_override) in item_elems(&doc) {
+        // log::debug!("{:?}", item_elem.attribute("identifier"));
+        let price_elem = item_elem
+            .children()
+            .find(|p| p.tag_name().name() == "Price");
+        let price_elem = match price_elem {
+            Some(p) => p,
+            None => continue,
+        };
+        let fabricate_elem = item_elem
+            .children()
+            .find(|p| p.tag_name().name() == "Fabricate");
+        let deconstruct_elem = item_elem
+            .children()
+            .find(|p| p.tag_name().name() == "Deconstruct");
+
+        let id = item_elem.attribute("identifier").unwrap().to_string();
+        let name = item_name(item_elem, &id, localization);
+        let level_resource = item_elem
+            .children()
+            .find(|x| x.tag_name().name() == "LevelResource")
+            .map(|elem| parse_level_resource(elem));
+
+        let item = Item {
+            name,
+            id,
+            tags: item_elem
+                .attribute("Tags")
+                .unwrap_or("")
+                .split(",")
+                .map(|x| x.to_string())
+                .collect(),
+            prices: parse_prices(price_elem),
+            fabricate: fabricate_elem.map(|e| parse_fabricate(e)),
+            deconstruct: deconstruct_elem.map(|e| parse_deconstruct(e)),
+            has_inventory_icon: item_elem
+                .children()
+                .find(|x| x.tag_name().name() == "InventoryIcon")
+                .is_some(),
+            has_sprite: item_elem
+                .children()
+                .find(|x| x.tag_name().name() == "Sprite")
+                .is_some(),
+            level_resource,
+            unlocks_recipes: item_elem
+                .descendants()
+                .filter(|x| x.tag_name().name().eq_ignore_ascii_case("addedrecipe"))
+                .filter_map(|x| attr_ci(x, "itemidentifier"))
+                .map(|x| x.to_string())
+                .collect(),
+        };
+
+        items.push((item, is_override))
+    }
+    items
+}
 
+pub(crate) fn item_files(game_path: &Path) -> Vec<std::path::PathBuf> {
     let items_path = game_path.join("Content").join("Items");
-    let mut items: Vec<Item> = vec![];
-    for entry in walkdir::WalkDir::new(items_path)
+    walkdir::WalkDir::new(items_path)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(|entry| entry.ok())
         .filter(|entry| {
@@ -228,83 +432,932 @@ pub(crate) fn parse_items(game_path: impl AsRef<Path>, localization: &Localizati
                 .unwrap_or(false)
                 && entry.path().file_name().unwrap().to_string_lossy() != "uniqueitems.xml"
         })
-    {
-        // log::debug!("{}", entry.path().display());
-        let content = std::fs::read_to_string(entry.path()).unwrap();
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+// keeps a single definition of every identifier: the last <Override>, wherever it is in path order,
+// or the first one if there is no override
+fn resolve_duplicates(
+    game_path: &Path,
+    files: Vec<(std::path::PathBuf, Vec<(Item, bool)>)>,
+) -> (Vec<Item>, Vec<DuplicateItem>) {
+    let mut items: Vec<Item> = vec![];
+    // id -> (index in items, report)
+    let mut seen: HashMap<String, (usize, DuplicateItem)> = HashMap::new();
+    for (path, file_items) in files {
+        let path = path.strip_prefix(game_path).unwrap_or(&path).to_owned();
+        for (item, is_override) in file_items {
+            match seen.get_mut(&item.id) {
+                None => {
+                    let report = DuplicateItem {
+                        id: item.id.clone(),
+                        kept: path.clone(),
+                        ignored: vec![],
+                        overridden: is_override,
+                    };
+                    seen.insert(item.id.clone(), (items.len(), report));
+                    items.push(item);
+                }
+                Some((idx, report)) if is_override => {
+                    // the overriding item takes the original's place, so that ordering doesn't change
+                    report
+                        .ignored
+                        .push(std::mem::replace(&mut report.kept, path.clone()));
+                    report.overridden = true;
+                    items[*idx] = item;
+                }
+                Some((_, report)) if report.overridden => {
+                    // the original of an override that comes first in path order
+                    report.ignored.push(path.clone());
+                }
+                Some((_, report)) => {
+                    log::warn!(
+                        "{} is defined in both {} and {} without an <Override>, keeping the first one",
+                        item.id,
+                        report.kept.display(),
+                        path.display()
+                    );
+                    report.ignored.push(path.clone());
+                }
+            }
+        }
+    }
+    let mut duplicates = seen
+        .into_iter()
+        .map(|(_, (_, report))| report)
+        .filter(|report| !report.ignored.is_empty())
+        .collect::<Vec<_>>();
+    duplicates.sort_by(|a, b| a.id.cmp(&b.id));
+    (items, duplicates)
+}
+
+pub(crate) fn parse_items(
+    game_path: impl AsRef<Path>,
+    localization: &Localization,
+) -> (Vec<Item>, Vec<DuplicateItem>) {
+    let game_path = game_path.as_ref();
+    let paths = item_files(game_path);
+    // files are parsed in parallel, but collected in path order, so the result is the same as a sequential parse
+    let parsed = paths
+        .par_iter()
+        .map(|path| parse_item_file(path, localization))
+        .collect::<Vec<_>>();
+    resolve_duplicates(game_path, paths.into_iter().zip(parsed).collect())
+}
+
+// character files are inconsistent about capitalization, both in element and attribute names
+fn attr_ci<'a>(elem: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    elem.attributes()
+        .iter()
+        .find(|a| a.name().eq_ignore_ascii_case(name))
+        .map(|a| a.value())
+}
+
+fn children_ci<'a, 'input: 'a>(
+    elem: roxmltree::Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> + 'a {
+    elem.children()
+        .filter(move |x| x.tag_name().name().eq_ignore_ascii_case(name))
+}
+
+fn parse_attack(elem: roxmltree::Node) -> Attack {
+    let parse_f32 = |name| {
+        attr_ci(elem, name)
+            .map(|x| x.parse().unwrap())
+            .unwrap_or(0.0)
+    };
+    Attack {
+        range: parse_f32("range"),
+        structure_damage: parse_f32("structuredamage"),
+        afflictions: children_ci(elem, "affliction")
+            .map(|a| {
+                (
+                    attr_ci(a, "identifier").unwrap().to_lowercase(),
+                    attr_ci(a, "strength").unwrap_or("0").parse().unwrap(),
+                )
+            })
+            .collect(),
+    }
+}
+
+fn parse_limbs(ragdoll_elem: roxmltree::Node) -> Vec<Limb> {
+    children_ci(ragdoll_elem, "limb")
+        .map(|limb| Limb {
+            name: attr_ci(limb, "name")
+                .or_else(|| attr_ci(limb, "type"))
+                .unwrap_or("")
+                .to_string(),
+            attack: children_ci(limb, "attack").next().map(parse_attack),
+        })
+        .collect()
+}
+
+fn xml_files(dir: &Path, recursive: bool) -> Vec<std::path::PathBuf> {
+    files_with_extension(dir, "xml", recursive)
+}
+
+// sorted, so that the output doesn't depend on the file system
+pub(crate) fn files_with_extension(
+    dir: &Path,
+    extension: &str,
+    recursive: bool,
+) -> Vec<std::path::PathBuf> {
+    let mut walker = walkdir::WalkDir::new(dir);
+    if !recursive {
+        walker = walker.max_depth(1);
+    }
+    let mut files = walker
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().to_owned())
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext.to_string_lossy() == extension)
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+// the element the file is about, looking through <Override> if there is one
+pub(crate) fn content_root<'a, 'input>(
+    doc: &'a roxmltree::Document<'input>,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    let root = doc.root_element();
+    if root.tag_name().name().eq_ignore_ascii_case("override") {
+        root.children().find(|x| x.is_element())
+    } else {
+        Some(root)
+    }
+}
+
+fn parse_creature(
+    char_elem: roxmltree::Node,
+    char_dir: &Path,
+    localization: &Localization,
+) -> Creature {
+    let id = attr_ci(char_elem, "speciesname").unwrap().to_lowercase();
+
+    // limbs (and their attacks) live either inline or in a separate ragdoll file
+    let mut limbs = children_ci(char_elem, "ragdoll")
+        .next()
+        .map(parse_limbs)
+        .unwrap_or_default();
+    if limbs.is_empty() {
+        let ragdoll_files = xml_files(&char_dir.join("Ragdolls"), false);
+        // the default ragdoll is the one the creature spawns with
+        let ragdoll_file = ragdoll_files
+            .iter()
+            .find(|p| p.to_string_lossy().to_lowercase().contains("default"))
+            .or_else(|| ragdoll_files.first());
+        if let Some(path) = ragdoll_file {
+            let content = std::fs::read_to_string(path).unwrap();
+            let doc = roxmltree::Document::parse(&content).unwrap();
+            limbs = parse_limbs(doc.root_element());
+        }
+    }
+
+    let mut speeds = BTreeMap::new();
+    for path in xml_files(&char_dir.join("Animations"), false) {
+        let content = std::fs::read_to_string(&path).unwrap();
         let doc = roxmltree::Document::parse(&content).unwrap();
-        let item_container_elem = match doc
-            .root()
-            .children()
-            .filter(|elem| elem.tag_name().name() == "Items")
+        let anim = doc.root_element();
+        let speed = match attr_ci(anim, "movementspeed") {
+            Some(s) => s.parse::<f32>().unwrap(),
+            None => continue,
+        };
+        let anim_type = attr_ci(anim, "animationtype")
+            .map(|x| x.to_lowercase())
+            .unwrap_or_else(|| path.file_stem().unwrap().to_string_lossy().to_lowercase());
+        let entry = speeds.entry(anim_type).or_insert(speed);
+        *entry = f32::max(*entry, speed);
+    }
+
+    let inventory = children_ci(char_elem, "inventory")
+        .flat_map(|inv| children_ci(inv, "item"))
+        .filter_map(|x| attr_ci(x, "identifier").map(|id| id.to_string()))
+        .collect::<Vec<_>>();
+
+    Creature {
+        name: localization.character_name_opt(&id).map(|x| x.to_string()),
+        has_info: attr_ci(char_elem, "hasinfo")
+            .map(|x| parse_bool(&x.to_lowercase()))
+            .unwrap_or(false),
+        health: children_ci(char_elem, "health")
             .next()
+            .and_then(|h| attr_ci(h, "vitality"))
+            .map(|x| x.parse().unwrap()),
+        limbs,
+        ai_targets: children_ci(char_elem, "ai")
+            .flat_map(|ai| children_ci(ai, "target"))
+            .map(|t| AiTarget {
+                tag: attr_ci(t, "tag").unwrap_or("").to_string(),
+                state: attr_ci(t, "state").unwrap_or("").to_string(),
+                priority: attr_ci(t, "priority").unwrap_or("0").parse().unwrap(),
+            })
+            .collect(),
+        speeds,
+        inventory: dedup_things(&inventory),
+        id,
+    }
+}
+
+pub(crate) fn parse_creatures(
+    game_path: impl AsRef<Path>,
+    localization: &Localization,
+) -> Vec<Creature> {
+    let chars_path = game_path.as_ref().join("Content").join("Characters");
+    let mut creatures = vec![];
+    for path in xml_files(&chars_path, true) {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&content).unwrap();
+        let char_elem = match content_root(&doc) {
+            Some(x) => x,
+            None => continue,
+        };
+        // ragdolls, animations and so on sit in the same folder
+        if !char_elem
+            .tag_name()
+            .name()
+            .eq_ignore_ascii_case("character")
         {
+            continue;
+        }
+        creatures.push(parse_creature(
+            char_elem,
+            path.parent().unwrap(),
+            localization,
+        ));
+    }
+    creatures
+}
+
+fn resolve_recipe_unlocks(items: &mut [Item], talents: &[Talent]) {
+    let mut unlocks: HashMap<String, Vec<RecipeUnlock>> = HashMap::new();
+    for talent in talents {
+        for id in &talent.unlocked_recipes {
+            let unlock = RecipeUnlock::Talent(talent.id.clone());
+            unlocks.entry(id.clone()).or_default().push(unlock);
+        }
+    }
+    for item in items.iter() {
+        for id in &item.unlocks_recipes {
+            let unlock = RecipeUnlock::Item(item.id.clone());
+            unlocks.entry(id.clone()).or_default().push(unlock);
+        }
+    }
+    for item in items.iter_mut() {
+        if let (Some(fab), Some(u)) = (item.fabricate.as_mut(), unlocks.get(&item.id)) {
+            fab.unlocked_by = u.clone();
+        }
+        let fab = match item.fabricate.as_ref() {
+            Some(f) => f,
+            None => continue,
+        };
+        if fab.requires_recipe && fab.unlocked_by.is_empty() {
+            log::warn!("{} requires a recipe, but nothing unlocks it", item.id);
+        }
+    }
+}
+
+// "50,60" is a range, "50" is a fixed level
+fn parse_skill_range(s: &str) -> (i32, i32) {
+    let mut parts = s
+        .split(',')
+        .map(|x| x.trim().parse::<f32>().unwrap() as i32);
+    let min = parts.next().unwrap();
+    let max = parts.next().unwrap_or(min);
+    (min, max)
+}
+
+pub(crate) fn parse_jobs(game_path: impl AsRef<Path>, localization: &Localization) -> Vec<Job> {
+    let path = game_path.as_ref().join("Content").join("Jobs.xml");
+    let content = match std::fs::read_to_string(&path) {
+        Ok(x) => x,
+        Err(e) => {
+            log::warn!("couldn't read {}: {}", path.display(), e);
+            return vec![];
+        }
+    };
+    let doc = roxmltree::Document::parse(&content).unwrap();
+    children_ci(doc.root_element(), "job")
+        .map(|job_elem| {
+            let id = attr_ci(job_elem, "identifier").unwrap().to_lowercase();
+            let skills = children_ci(job_elem, "skills")
+                .flat_map(|s| children_ci(s, "skill"))
+                .map(|skill| {
+                    let (min, max) = parse_skill_range(attr_ci(skill, "level").unwrap());
+                    let skill_id = attr_ci(skill, "identifier")
+                        .or_else(|| attr_ci(skill, "name"))
+                        .unwrap()
+                        .to_lowercase();
+                    (skill_id, min, max)
+                })
+                .collect();
+            // starting items can be nested, e.g. things inside a toolbelt
+            let items = children_ci(job_elem, "items")
+                .flat_map(|items| items.descendants())
+                .filter(|x| x.tag_name().name().eq_ignore_ascii_case("item"))
+                .filter_map(|x| attr_ci(x, "identifier").map(|id| id.to_string()))
+                .collect::<Vec<_>>();
+            Job {
+                name: localization
+                    .job_name_opt(&id)
+                    .or_else(|| attr_ci(job_elem, "name"))
+                    .map(|x| x.to_string()),
+                skills,
+                items: dedup_things(&items),
+                id,
+            }
+        })
+        .collect()
+}
+
+fn parse_talent_tree(elem: roxmltree::Node, localization: &Localization) -> TalentTree {
+    TalentTree {
+        job: attr_ci(elem, "jobidentifier").unwrap().to_lowercase(),
+        subtrees: children_ci(elem, "subtree")
+            .map(|sub| {
+                let id = attr_ci(sub, "identifier").unwrap().to_lowercase();
+                TalentSubTree {
+                    name: localization
+                        .get_opt(&format!("talenttree.{}", id))
+                        .map(|x| x.to_string()),
+                    options: children_ci(sub, "talentoption")
+                        .map(|opt| {
+                            children_ci(opt, "talent")
+                                .filter_map(|t| attr_ci(t, "identifier"))
+                                .map(|x| x.to_lowercase())
+                                .collect()
+                        })
+                        .collect(),
+                    id,
+                }
+            })
+            .collect(),
+    }
+}
+
+fn parse_talent(elem: roxmltree::Node, localization: &Localization) -> Talent {
+    let id = attr_ci(elem, "identifier").unwrap().to_lowercase();
+    let description_key = children_ci(elem, "description")
+        .next()
+        .and_then(|d| attr_ci(d, "tag"))
+        .map(|x| x.to_lowercase())
+        .unwrap_or_else(|| format!("talentdescription.{}", id));
+    // stat changes are spread over various ability types, but all of them use the same attribute names
+    let stats = elem
+        .descendants()
+        .filter_map(|x| Some((attr_ci(x, "stattype")?, attr_ci(x, "value")?)))
+        .filter_map(|(stat, value)| match value.parse() {
+            Ok(value) => Some((stat.to_string(), value)),
+            Err(_) => {
+                log::warn!("ignoring {} {} of talent {}: not a number", stat, value, id);
+                None
+            }
+        })
+        .collect();
+    Talent {
+        name: localization
+            .get_opt(&format!("talentname.{}", id))
+            .map(|x| x.to_string()),
+        description: localization
+            .get_opt(&description_key)
+            .map(|x| x.to_string()),
+        unlocked_recipes: elem
+            .descendants()
+            .filter(|x| x.tag_name().name().eq_ignore_ascii_case("addedrecipe"))
+            .filter_map(|x| attr_ci(x, "itemidentifier"))
+            .map(|x| x.to_string())
+            .collect(),
+        stats,
+        id,
+    }
+}
+
+pub(crate) fn parse_talents(
+    game_path: impl AsRef<Path>,
+    localization: &Localization,
+) -> (Vec<TalentTree>, Vec<Talent>) {
+    let talents_path = game_path.as_ref().join("Content").join("Talents");
+    let mut trees = vec![];
+    let mut talents = vec![];
+    for path in xml_files(&talents_path, true) {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&content).unwrap();
+        let root = match content_root(&doc) {
             Some(x) => x,
             None => continue,
         };
-        for item_elem in item_container_elem
-            .children()
-            .filter(|elem| elem.is_element())
+        for tree in children_ci(root, "talenttree") {
+            trees.push(parse_talent_tree(tree, localization));
+        }
+        for talent in children_ci(root, "talent") {
+            talents.push(parse_talent(talent, localization));
+        }
+    }
+    (trees, talents)
+}
+
+fn parse_mission(elem: roxmltree::Node, localization: &Localization) -> Mission {
+    let id = attr_ci(elem, "identifier").unwrap().to_string();
+    let kind = elem.tag_name().name().to_lowercase();
+    let kind = kind.trim_end_matches("mission").to_string();
+    // same precedence as item names: explicit text, then text identifier, then identifier
+    let text_id = attr_ci(elem, "textidentifier")
+        .unwrap_or(&id)
+        .to_lowercase();
+    let name = attr_ci(elem, "name")
+        .filter(|x| !x.is_empty())
+        .or_else(|| localization.get_opt(&format!("missionname.{}", text_id)))
+        .map(|x| x.to_string());
+    let description = attr_ci(elem, "description")
+        .filter(|x| !x.is_empty())
+        .or_else(|| localization.get_opt(&format!("missiondescription.{}", text_id)))
+        .map(|x| x.to_string());
+
+    let mut required_items = ["item", "itemidentifier"]
+        .iter()
+        .filter_map(|a| attr_ci(elem, a))
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>();
+    required_items.extend(
+        elem.descendants()
+            .filter(|x| x.tag_name().name().eq_ignore_ascii_case("item"))
+            .filter_map(|x| attr_ci(x, "identifier"))
+            .map(|x| x.to_string()),
+    );
+    // the same item can be both an attribute and a descendant, or in several places
+    let mut seen = HashSet::new();
+    required_items.retain(|x| seen.insert(x.clone()));
+
+    let monsters = elem
+        .descendants()
+        .filter(|x| x.tag_name().name().eq_ignore_ascii_case("monster"))
+        .filter_map(|x| {
+            let species = attr_ci(x, "character").or_else(|| attr_ci(x, "identifier"))?;
+            let amount = attr_ci(x, "amount").unwrap_or("1").parse().unwrap();
+            Some((species.to_lowercase(), amount))
+        })
+        .collect();
+
+    Mission {
+        kind,
+        name,
+        description,
+        reward: attr_ci(elem, "reward").unwrap_or("0").parse().unwrap(),
+        reputation: children_ci(elem, "reputation")
+            .map(|r| {
+                let faction = attr_ci(r, "identifier")
+                    .or_else(|| attr_ci(r, "faction"))
+                    .unwrap();
+                (
+                    faction.to_lowercase(),
+                    attr_ci(r, "amount").unwrap().parse().unwrap(),
+                )
+            })
+            .collect(),
+        location_types: children_ci(elem, "locationtype")
+            .map(|lt| {
+                (
+                    attr_ci(lt, "from").unwrap_or("any").to_lowercase(),
+                    attr_ci(lt, "to").unwrap_or("any").to_lowercase(),
+                )
+            })
+            .collect(),
+        required_items,
+        monsters,
+        id,
+    }
+}
+
+pub(crate) fn parse_missions(
+    game_path: impl AsRef<Path>,
+    localization: &Localization,
+) -> Vec<Mission> {
+    let missions_path = game_path.as_ref().join("Content").join("Missions");
+    let mut missions = vec![];
+    for path in xml_files(&missions_path, true) {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&content).unwrap();
+        let root = match content_root(&doc) {
+            Some(x) => x,
+            None => continue,
+        };
+        for elem in root.children().filter(|x| x.is_element()) {
+            if !elem.tag_name().name().to_lowercase().ends_with("mission") {
+                continue;
+            }
+            missions.push(parse_mission(elem, localization));
+        }
+    }
+    missions
+}
+
+/// Location types, biomes and level generation parameters, all of which live in Content/Map.
+pub(crate) fn parse_map(
+    game_path: impl AsRef<Path>,
+    localization: &Localization,
+) -> (Vec<LocationType>, Vec<Biome>, Vec<LevelGenerationParams>) {
+    let map_path = game_path.as_ref().join("Content").join("Map");
+    let mut location_types = vec![];
+    let mut biomes = vec![];
+    let mut level_params = vec![];
+    for path in xml_files(&map_path, true) {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&content).unwrap();
+        let root = match content_root(&doc) {
+            Some(x) => x,
+            None => continue,
+        };
+        if root.tag_name().name().eq_ignore_ascii_case("locationtypes") {
+            for elem in root.children().filter(|x| x.is_element()) {
+                // older versions name the element after the location type instead of having an identifier
+                let id = attr_ci(elem, "identifier")
+                    .map(|x| x.to_lowercase())
+                    .unwrap_or_else(|| elem.tag_name().name().to_lowercase());
+                location_types.push(LocationType {
+                    name: localization
+                        .get_opt(&format!("locationname.{}", id))
+                        .or_else(|| attr_ci(elem, "name"))
+                        .map(|x| x.to_string()),
+                    sell_price_modifier: attr_ci(elem, "storesellpricemodifier")
+                        .map(|x| x.parse().unwrap())
+                        .unwrap_or(LocationType::DEFAULT_SELL_PRICE_MODIFIER),
+                    id,
+                });
+            }
+        }
+        if root
+            .tag_name()
+            .name()
+            .eq_ignore_ascii_case("levelgenerationparameters")
         {
-            // log::debug!("{:?}", item_elem.attribute("identifier"));
-            let price_elem = item_elem
-                .children()
-                .filter(|p| p.tag_name().name() == "Price")
-                .next();
-            let price_elem = match price_elem {
-                Some(p) => p,
+            for elem in root.children().filter(|x| x.is_element()) {
+                let biome_ids = match attr_ci(elem, "biomes") {
+                    Some(x) => x,
+                    None => continue,
+                };
+                level_params.push(LevelGenerationParams {
+                    id: attr_ci(elem, "identifier")
+                        .map(|x| x.to_lowercase())
+                        .unwrap_or_else(|| elem.tag_name().name().to_lowercase()),
+                    biomes: biome_ids
+                        .split(',')
+                        .map(|x| x.trim().to_lowercase())
+                        .filter(|x| !x.is_empty())
+                        .collect(),
+                });
+            }
+        }
+        // biomes are either in their own file or a part of the level generation parameters
+        for elem in root
+            .descendants()
+            .filter(|x| x.tag_name().name().eq_ignore_ascii_case("biome"))
+        {
+            let id = match attr_ci(elem, "identifier") {
+                Some(x) => x.to_lowercase(),
                 None => continue,
             };
-            let fabricate_elem = item_elem
-                .children()
-                .filter(|p| p.tag_name().name() == "Fabricate")
-                .next();
-            let deconstruct_elem = item_elem
-                .children()
-                .filter(|p| p.tag_name().name() == "Deconstruct")
-                .next();
+            biomes.push(Biome {
+                name: localization
+                    .get_opt(&format!("biomename.{}", id))
+                    .or_else(|| attr_ci(elem, "name"))
+                    .map(|x| x.to_string()),
+                id,
+            });
+        }
+    }
+    (location_types, biomes, level_params)
+}
+
+// .sub files are gzipped xml, but accept plain xml too since that's what the editor can save as well
+fn read_submarine_file(path: &Path) -> std::io::Result<String> {
+    use std::io::Read;
+    let bytes = std::fs::read(path)?;
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut content = String::new();
+        flate2::read::GzDecoder::new(bytes.as_slice()).read_to_string(&mut content)?;
+        Ok(content)
+    } else {
+        String::from_utf8(bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+// parses an optional attribute, an error if it's there but malformed
+fn parse_attr<T: std::str::FromStr>(
+    elem: roxmltree::Node,
+    name: &str,
+) -> Result<Option<T>, String> {
+    attr_ci(elem, name)
+        .map(|x| {
+            x.trim()
+                .parse()
+                .map_err(|_| format!("invalid {} {:?}", name, x))
+        })
+        .transpose()
+}
+
+fn parse_submarine(path: &Path) -> Result<Submarine, String> {
+    let content = read_submarine_file(path).map_err(|e| e.to_string())?;
+    let doc = roxmltree::Document::parse(&content).map_err(|e| e.to_string())?;
+    let elem = doc.root_element();
 
-            let id = item_elem.attribute("identifier").unwrap().to_string();
-            let mut name: Option<String> = item_elem.attribute("name").map(|x| x.to_string());
-            if name.as_deref() == Some("") {
-                name = None;
+    let dimensions = match attr_ci(elem, "dimensions") {
+        Some(s) => {
+            let parts = s
+                .split(',')
+                .map(|x| x.trim().parse::<f32>())
+                .collect::<Result<Vec<_>, _>>();
+            match parts.as_deref() {
+                Ok(&[w, h]) => Some((w, h)),
+                _ => return Err(format!("invalid dimensions {:?}", s)),
             }
-            if name.is_none() {
-                name = item_elem
-                    .attribute("nameidentifier")
-                    .and_then(|nid| localization.item_name_opt(nid))
-                    .or_else(|| localization.item_name_opt(id.as_str()))
-                    .map(|x| x.to_string());
-            };
-            let level_resource = item_elem
-                .children()
-                .find(|x| x.tag_name().name() == "LevelResource")
-                .map(|elem| parse_level_resource(elem));
+        }
+        None => None,
+    };
+    let crew_min = parse_attr(elem, "recommendedcrewsizemin")?;
+    let crew_max = parse_attr(elem, "recommendedcrewsizemax")?;
+
+    // items inside containers are still top level elements, just with a reference to the container
+    let items = children_ci(elem, "item")
+        .filter_map(|x| attr_ci(x, "identifier"))
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>();
+    let mut items = dedup_things(&items);
+    items.sort();
+
+    Ok(Submarine {
+        name: attr_ci(elem, "name")
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| path.file_stem().unwrap().to_string_lossy().to_string()),
+        description: attr_ci(elem, "description")
+            .filter(|x| !x.is_empty())
+            .map(|x| x.to_string()),
+        class: attr_ci(elem, "class").map(|x| x.to_string()),
+        tier: parse_attr(elem, "tier")?,
+        price: parse_attr(elem, "price")?,
+        crew_size: crew_min.map(|min| (min, crew_max.unwrap_or(min))),
+        dimensions,
+        items,
+    })
+}
+
+pub(crate) fn parse_submarines(game_path: impl AsRef<Path>) -> Vec<Submarine> {
+    let subs_path = game_path.as_ref().join("Content").join("Submarines");
+    files_with_extension(&subs_path, "sub", true)
+        .iter()
+        .filter_map(|path| match parse_submarine(path) {
+            Ok(x) => Some(x),
+            Err(e) => {
+                log::warn!("skipping submarine {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+// none, with a warning, if the max level or the price can't be read
+fn parse_upgrade_module(
+    elem: roxmltree::Node,
+    localization: &Localization,
+) -> Option<UpgradeModule> {
+    let id = attr_ci(elem, "identifier").unwrap().to_lowercase();
+    let max_level = match attr_ci(elem, "maxlevel").map(|x| x.parse()) {
+        Some(Ok(x)) => x,
+        _ => {
+            log::warn!("skipping upgrade {} without a valid maxlevel", id);
+            return None;
+        }
+    };
+    let price = match children_ci(elem, "price")
+        .next()
+        .and_then(parse_upgrade_price)
+    {
+        Some(x) => x,
+        None => {
+            log::warn!("skipping upgrade {} without a valid price", id);
+            return None;
+        }
+    };
+    let properties = elem
+        .children()
+        .filter(|x| x.is_element())
+        .filter(|x| !["price", "maxlevel"].contains(&x.tag_name().name().to_lowercase().as_str()))
+        .flat_map(|target| {
+            target.attributes().iter().map(move |attr| {
+                (
+                    target.tag_name().name().to_lowercase(),
+                    attr.name().to_lowercase(),
+                    attr.value().to_string(),
+                )
+            })
+        })
+        .collect();
+    Some(UpgradeModule {
+        name: localization
+            .get_opt(&format!("upgradename.{}", id))
+            .or_else(|| attr_ci(elem, "name"))
+            .map(|x| x.to_string()),
+        categories: attr_ci(elem, "category")
+            .unwrap_or("")
+            .split(',')
+            .map(|x| x.trim().to_lowercase())
+            .filter(|x| !x.is_empty())
+            .collect(),
+        max_level,
+        class_max_levels: children_ci(elem, "maxlevel")
+            .filter_map(|ml| {
+                Some((
+                    attr_ci(ml, "class")?.to_lowercase(),
+                    attr_ci(ml, "level")
+                        .or_else(|| attr_ci(ml, "value"))?
+                        .parse()
+                        .ok()?,
+                ))
+            })
+            .collect(),
+        price,
+        properties,
+        id,
+    })
+}
 
-            let item = Item {
-                name,
+pub(crate) fn parse_upgrades(
+    game_path: impl AsRef<Path>,
+    localization: &Localization,
+) -> (Vec<UpgradeCategory>, Vec<UpgradeModule>) {
+    let upgrades_path = game_path.as_ref().join("Content").join("UpgradeModules");
+    let mut categories = vec![];
+    let mut modules = vec![];
+    for path in xml_files(&upgrades_path, true) {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&content).unwrap();
+        let root = match content_root(&doc) {
+            Some(x) => x,
+            None => continue,
+        };
+        for elem in children_ci(root, "upgradecategory") {
+            let id = attr_ci(elem, "identifier").unwrap().to_lowercase();
+            categories.push(UpgradeCategory {
+                name: localization
+                    .get_opt(&format!("upgradecategory.{}", id))
+                    .or_else(|| attr_ci(elem, "name"))
+                    .map(|x| x.to_string()),
                 id,
-                tags: item_elem
-                    .attribute("Tags")
-                    .unwrap_or("")
-                    .split(",")
-                    .map(|x| x.to_string())
+            });
+        }
+        modules.extend(
+            children_ci(root, "upgrademodule")
+                .filter_map(|elem| parse_upgrade_module(elem, localization)),
+        );
+    }
+    (categories, modules)
+}
+
+fn split_ids(list: &str) -> impl Iterator<Item = String> + '_ {
+    list.split(',')
+        .map(|x| x.trim().to_lowercase())
+        .filter(|x| !x.is_empty())
+}
+
+pub(crate) fn parse_outposts(game_path: impl AsRef<Path>) -> Vec<OutpostParams> {
+    let map_path = game_path.as_ref().join("Content").join("Map");
+    let mut outposts = vec![];
+    for path in xml_files(&map_path, true) {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&content).unwrap();
+        let root = match content_root(&doc) {
+            Some(x) => x,
+            None => continue,
+        };
+        if !root
+            .tag_name()
+            .name()
+            .eq_ignore_ascii_case("outpostgenerationparameters")
+        {
+            continue;
+        }
+        for elem in root.children().filter(|x| x.is_element()) {
+            outposts.push(OutpostParams {
+                id: attr_ci(elem, "identifier")
+                    .map(|x| x.to_lowercase())
+                    .unwrap_or_else(|| elem.tag_name().name().to_lowercase()),
+                location_types: split_ids(attr_ci(elem, "allowedlocationtypes").unwrap_or(""))
+                    .filter(|x| x != "any")
                     .collect(),
-                prices: parse_prices(price_elem),
-                fabricate: fabricate_elem.map(|e| parse_fabricate(e)),
-                deconstruct: deconstruct_elem.map(|e| parse_deconstruct(e)),
-                has_inventory_icon: item_elem
-                    .children()
-                    .find(|x| x.tag_name().name() == "InventoryIcon")
-                    .is_some(),
-                has_sprite: item_elem
-                    .children()
-                    .find(|x| x.tag_name().name() == "Sprite")
-                    .is_some(),
-                level_resource,
-            };
+                module_counts: children_ci(elem, "modulecount")
+                    .map(|mc| {
+                        (
+                            attr_ci(mc, "flag")
+                                .or_else(|| attr_ci(mc, "moduletype"))
+                                .unwrap()
+                                .to_lowercase(),
+                            attr_ci(mc, "count").unwrap_or("1").parse().unwrap(),
+                        )
+                    })
+                    .collect(),
+            });
+        }
+    }
+    outposts
+}
 
-            items.push(item)
+fn is_event_elem(elem: roxmltree::Node) -> bool {
+    elem.is_element()
+        && elem.tag_name().name().to_lowercase().ends_with("event")
+        && attr_ci(elem, "identifier").is_some()
+}
+
+// items mentioned by the event's actions, either by identifier or by tag
+fn event_items(elem: roxmltree::Node, actions: &[&str]) -> Vec<RequiredItem> {
+    let mut result = vec![];
+    for action in elem
+        .descendants()
+        .filter(|x| actions.contains(&x.tag_name().name().to_lowercase().as_str()))
+    {
+        for attr in &["itemidentifier", "itemidentifiers"] {
+            result.extend(split_ids(attr_ci(action, attr).unwrap_or("")).map(RequiredItem::Id));
+        }
+        for attr in &["itemtag", "itemtags"] {
+            result.extend(split_ids(attr_ci(action, attr).unwrap_or("")).map(RequiredItem::Tag));
         }
     }
-    items
+    result.sort();
+    result.dedup();
+    result
+}
+
+fn parse_event(elem: roxmltree::Node) -> Event {
+    Event {
+        id: attr_ci(elem, "identifier").unwrap().to_lowercase(),
+        gives: event_items(elem, &["spawnaction"]),
+        requires: event_items(elem, &["checkitemaction", "removeitemaction"]),
+    }
+}
+
+fn parse_event_set(
+    elem: roxmltree::Node,
+    parent_location_types: &[String],
+    events: &mut Vec<Event>,
+    sets: &mut Vec<EventSet>,
+) {
+    let location_types = match attr_ci(elem, "locationtype") {
+        Some(x) => split_ids(x).filter(|x| x != "any").collect(),
+        None => parent_location_types.to_vec(),
+    };
+    let mut set = EventSet {
+        location_types,
+        events: vec![],
+    };
+    for child in elem.children().filter(|x| x.is_element()) {
+        if child.tag_name().name().eq_ignore_ascii_case("eventset") {
+            parse_event_set(child, &set.location_types, events, sets);
+        } else if is_event_elem(child) {
+            // sets either reference an event by identifier, or define it inline
+            if child.children().any(|x| x.is_element()) {
+                events.push(parse_event(child));
+            }
+            set.events
+                .push(attr_ci(child, "identifier").unwrap().to_lowercase());
+        }
+    }
+    sets.push(set);
+}
+
+pub(crate) fn parse_events(game_path: impl AsRef<Path>) -> (Vec<Event>, Vec<EventSet>) {
+    let events_path = game_path.as_ref().join("Content").join("RandomEvents");
+    let mut events = vec![];
+    let mut sets = vec![];
+    for path in xml_files(&events_path, true) {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let doc = roxmltree::Document::parse(&content).unwrap();
+        let root = match content_root(&doc) {
+            Some(x) => x,
+            None => continue,
+        };
+        for prefabs in root
+            .descendants()
+            .filter(|x| x.tag_name().name().eq_ignore_ascii_case("eventprefabs"))
+        {
+            events.extend(
+                prefabs
+                    .children()
+                    .filter(|x| is_event_elem(*x))
+                    .map(parse_event),
+            );
+        }
+        // nested sets are parsed recursively
+        for elem in root
+            .children()
+            .filter(|x| x.tag_name().name().eq_ignore_ascii_case("eventset"))
+        {
+            parse_event_set(elem, &[], &mut events, &mut sets);
+        }
+    }
+    (events, sets)
 }
@@ -0,0 +1,328 @@
+use barostuff::{Db, Item, RecipeUnlock, RequiredItem, UnknownItem};
+
+use serde::Serialize;
+
+use std::fmt::{self, Write};
+
+/// Item by identifier, or failing that by its name, ignoring case.
+pub(crate) fn find_item<'a>(db: &'a Db, query: &str) -> Result<&'a Item, UnknownItem> {
+    db.try_item(query).or_else(|e| {
+        db.items
+            .iter()
+            .find(|it| {
+                it.name
+                    .as_deref()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(query))
+            })
+            .ok_or(e)
+    })
+}
+
+/// An item referred to by the description, with its name if it has one.
+#[derive(Serialize)]
+pub(crate) struct ItemRef<'a> {
+    id: &'a str,
+    name: Option<&'a str>,
+}
+impl<'a> ItemRef<'a> {
+    fn new(db: &'a Db, id: &'a str) -> ItemRef<'a> {
+        ItemRef {
+            id,
+            name: db.item(id).and_then(|it| it.name.as_deref()),
+        }
+    }
+}
+// "Steel Bar (steel)", or just the id for items without a name
+impl fmt::Display for ItemRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{} ({})", name, self.id),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct Amount<'a> {
+    #[serde(flatten)]
+    item: ItemRef<'a>,
+    amount: i32,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum Material<'a> {
+    Item(Amount<'a>),
+    Tag { tag: &'a str, amount: i32 },
+}
+
+#[derive(Serialize)]
+pub(crate) struct LocationPrice<'a> {
+    location: &'a str,
+    location_name: &'a str,
+    price: f32,
+    multiplier: f32,
+    sold: bool,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Skill<'a> {
+    skill: &'a str,
+    name: String,
+    level: i32,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum Unlock<'a> {
+    Talent { talent: &'a str, name: &'a str },
+    Item(ItemRef<'a>),
+}
+
+#[derive(Serialize)]
+pub(crate) struct FabricateDescription<'a> {
+    fabricator: &'a str,
+    time: i32,
+    out_amount: i32,
+    mats: Vec<Material<'a>>,
+    skills: Vec<Skill<'a>>,
+    requires_recipe: bool,
+    unlocked_by: Vec<Unlock<'a>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct DeconstructDescription<'a> {
+    time: i32,
+    mats: Vec<Amount<'a>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct LevelResourceDescription<'a> {
+    commonness: f32,
+    // sorted by level type
+    level_types: Vec<(&'a str, f32)>,
+}
+
+/// Everything known about an item, printed as text or as json by `query`.
+#[derive(Serialize)]
+pub(crate) struct ItemDescription<'a> {
+    #[serde(flatten)]
+    item: ItemRef<'a>,
+    description: Option<&'a str>,
+    tags: Vec<&'a str>,
+    base_price: i32,
+    prices: Vec<LocationPrice<'a>>,
+    fabricate: Option<FabricateDescription<'a>>,
+    deconstruct: Option<DeconstructDescription<'a>>,
+    level_resource: Option<LevelResourceDescription<'a>>,
+    used_in: Vec<Amount<'a>>,
+    obtained_from: Vec<Amount<'a>>,
+    unlocks_recipes: Vec<ItemRef<'a>>,
+}
+
+fn amounts<'a>(db: &'a Db, list: Vec<(&'a str, i32)>) -> Vec<Amount<'a>> {
+    list.into_iter()
+        .map(|(id, amount)| Amount {
+            item: ItemRef::new(db, id),
+            amount,
+        })
+        .collect()
+}
+
+/// Collects what is known about the item.
+pub(crate) fn describe_item<'a>(db: &'a Db, item: &'a Item) -> ItemDescription<'a> {
+    let fabricate = item.fabricate.as_ref().map(|fab| FabricateDescription {
+        fabricator: &fab.fabricator,
+        time: fab.time,
+        out_amount: fab.out_amount,
+        mats: fab
+            .mats
+            .iter()
+            .map(|(mat, amount)| match mat {
+                RequiredItem::Id(id) => Material::Item(Amount {
+                    item: ItemRef::new(db, id),
+                    amount: *amount,
+                }),
+                RequiredItem::Tag(tag) => Material::Tag {
+                    tag,
+                    amount: *amount,
+                },
+            })
+            .collect(),
+        skills: fab
+            .skills
+            .iter()
+            .map(|(skill, level)| Skill {
+                skill,
+                name: db.localization.skill_name(skill),
+                level: *level,
+            })
+            .collect(),
+        requires_recipe: fab.requires_recipe,
+        unlocked_by: fab
+            .unlocked_by
+            .iter()
+            .map(|unlock| match unlock {
+                RecipeUnlock::Talent(id) => Unlock::Talent {
+                    talent: id,
+                    name: db.talent_name(id),
+                },
+                RecipeUnlock::Item(id) => Unlock::Item(ItemRef::new(db, id)),
+            })
+            .collect(),
+    });
+
+    let level_resource = item.level_resource.as_ref().map(|lr| {
+        let mut level_types = lr
+            .comonness
+            .iter()
+            .map(|(level_type, com)| (level_type.as_str(), *com))
+            .collect::<Vec<_>>();
+        level_types.sort_by(|a, b| a.0.cmp(b.0));
+        LevelResourceDescription {
+            commonness: lr.comonness_default,
+            level_types,
+        }
+    });
+
+    ItemDescription {
+        item: ItemRef::new(db, &item.id),
+        description: db.localization.item_description(&item.id),
+        // items without a Tags attribute have a single empty tag
+        tags: item
+            .tags
+            .iter()
+            .filter(|t| !t.is_empty())
+            .map(|t| t.as_str())
+            .collect(),
+        base_price: item.prices.base_price,
+        prices: item
+            .prices
+            .locations
+            .iter()
+            .map(|(loc, (multiplier, sold))| LocationPrice {
+                location: loc,
+                location_name: db.location_type_name(loc),
+                price: item.prices.price_at(loc).round(),
+                multiplier: *multiplier,
+                sold: *sold,
+            })
+            .collect(),
+        fabricate,
+        deconstruct: item
+            .deconstruct
+            .as_ref()
+            .map(|decon| DeconstructDescription {
+                time: decon.time,
+                mats: amounts(
+                    db,
+                    decon
+                        .mats
+                        .iter()
+                        .map(|(id, cnt)| (id.as_str(), *cnt))
+                        .collect(),
+                ),
+            }),
+        level_resource,
+        used_in: amounts(db, db.used_in(&item.id)),
+        obtained_from: amounts(db, db.obtained_from(&item.id)),
+        unlocks_recipes: item
+            .unlocks_recipes
+            .iter()
+            .map(|id| ItemRef::new(db, id))
+            .collect(),
+    }
+}
+
+fn format_list(list: &[Amount]) -> String {
+    list.iter()
+        .map(|a| format!("  {} x{}\n", a.item, a.amount))
+        .collect()
+}
+
+impl ItemDescription<'_> {
+    /// The description as plain text.
+    pub(crate) fn to_text(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "{}", self.item).unwrap();
+        if let Some(description) = self.description {
+            writeln!(out, "{}", description).unwrap();
+        }
+        out += "\n";
+
+        if !self.tags.is_empty() {
+            writeln!(out, "tags: {}", self.tags.join(", ")).unwrap();
+        }
+        writeln!(out, "base price: {}", self.base_price).unwrap();
+        if !self.prices.is_empty() {
+            out += "prices:\n";
+            for p in &self.prices {
+                writeln!(
+                    out,
+                    "  {}: {} (x{}){}",
+                    p.location_name,
+                    p.price,
+                    p.multiplier,
+                    if p.sold { "" } else { ", not sold" }
+                )
+                .unwrap();
+            }
+        }
+
+        if let Some(fab) = self.fabricate.as_ref() {
+            write!(out, "fabricate: {}, {} s", fab.fabricator, fab.time).unwrap();
+            if fab.out_amount > 1 {
+                write!(out, ", makes {}", fab.out_amount).unwrap();
+            }
+            out += "\n";
+            for mat in &fab.mats {
+                match mat {
+                    Material::Item(a) => writeln!(out, "  {} x{}", a.item, a.amount),
+                    Material::Tag { tag, amount } => writeln!(out, "  any {} x{}", tag, amount),
+                }
+                .unwrap();
+            }
+            for skill in &fab.skills {
+                writeln!(out, "  needs {} {}", skill.name, skill.level).unwrap();
+            }
+            if fab.requires_recipe && fab.unlocked_by.is_empty() {
+                out += "  needs a recipe unlock\n";
+            }
+            for unlock in &fab.unlocked_by {
+                match unlock {
+                    Unlock::Talent { name, .. } => writeln!(out, "  unlocked by talent {}", name),
+                    Unlock::Item(item) => writeln!(out, "  unlocked by {}", item),
+                }
+                .unwrap();
+            }
+        }
+
+        if let Some(decon) = self.deconstruct.as_ref() {
+            writeln!(out, "deconstruct: {} s", decon.time).unwrap();
+            out += &format_list(&decon.mats);
+        }
+
+        if let Some(lr) = self.level_resource.as_ref() {
+            writeln!(out, "level resource: commonness {}", lr.commonness).unwrap();
+            for (level_type, com) in &lr.level_types {
+                writeln!(out, "  {}: {}", level_type, com).unwrap();
+            }
+        }
+
+        if !self.used_in.is_empty() {
+            out += "used in:\n";
+            out += &format_list(&self.used_in);
+        }
+        if !self.obtained_from.is_empty() {
+            out += "obtained from:\n";
+            out += &format_list(&self.obtained_from);
+        }
+        if !self.unlocks_recipes.is_empty() {
+            out += "unlocks recipes:\n";
+            for item in &self.unlocks_recipes {
+                writeln!(out, "  {}", item).unwrap();
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,388 @@
+use std::path::{Path, PathBuf};
+
+fn copy_dir(from: &Path, to: &Path) {
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry.unwrap();
+        let target = to.join(entry.path().strip_prefix(from).unwrap());
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target).unwrap();
+        } else {
+            std::fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+}
+
+fn wrench_price(db: &barostuff::Db) -> i32 {
+    let wrench = db.items.iter().find(|it| it.id == "wrench").unwrap();
+    wrench.prices.base_price
+}
+
+#[test]
+fn cache_is_invalidated_by_changes() {
+    let work_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("cache");
+    let _ = std::fs::remove_dir_all(&work_dir);
+    let game_path = work_dir.join("game");
+    let cache_path = work_dir.join("db.json");
+    copy_dir(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/game"),
+        &game_path,
+    );
+
+    let db = barostuff::parse_db_cached(&game_path, &cache_path).unwrap();
+    assert!(cache_path.exists());
+    assert_eq!(wrench_price(&db), 100);
+
+    // served from the cache, including the usages that aren't stored in it
+    let cached = barostuff::parse_db_cached(&game_path, &cache_path).unwrap();
+    assert_eq!(cached.items.len(), db.items.len());
+    assert_eq!(cached.used_in("wire"), db.used_in("wire"));
+
+    let tools = game_path.join("Content/Items/Tools/tools.xml");
+    let content = std::fs::read_to_string(&tools).unwrap();
+    // changes the size too, since the mtime may not have moved on within the test
+    std::fs::write(&tools, content.replace(r#"baseprice="100""#, r#"baseprice="1500""#)).unwrap();
+    let changed = barostuff::parse_db_cached(&game_path, &cache_path).unwrap();
+    assert_eq!(wrench_price(&changed), 1500);
+}
+
+// Serializer that describes the types a value is serialized as, instead of its data.
+// Struct fields and enum variants are collected by type name, so that the description only depends on
+// the model, plus on which options and variants the fixture happens to use.
+mod shape {
+    use serde::ser::{self, Serialize};
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::fmt::Error;
+
+    #[derive(Default)]
+    pub struct Recorder {
+        // "Type.field" or "Enum::Variant" -> types seen
+        pub fields: BTreeMap<String, BTreeSet<String>>,
+    }
+
+    impl Recorder {
+        fn record(&mut self, key: String, ty: String) {
+            self.fields.entry(key).or_default().insert(ty);
+        }
+        fn describe<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<String, Error> {
+            value.serialize(self)
+        }
+    }
+
+    pub struct Compound<'a> {
+        recorder: &'a mut Recorder,
+        // type name, for structs and variants
+        name: String,
+        // "[", "(", or "{" for sequences, tuples and maps
+        open: &'static str,
+        // element types in order, only the distinct ones are kept for sequences and maps
+        elems: Vec<String>,
+        // for maps, the last key
+        key: String,
+    }
+
+    impl<'a> Compound<'a> {
+        fn new(recorder: &'a mut Recorder, name: String, open: &'static str) -> Self {
+            Compound {
+                recorder,
+                name,
+                open,
+                elems: vec![],
+                key: String::new(),
+            }
+        }
+        fn elem<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+            let ty = self.recorder.describe(value)?;
+            self.elems.push(ty);
+            Ok(())
+        }
+        fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+            let ty = self.recorder.describe(value)?;
+            self.recorder.record(format!("{}.{}", self.name, key), ty);
+            Ok(())
+        }
+        fn collection(mut self) -> Result<String, Error> {
+            let close = match self.open {
+                "[" => "]",
+                "(" => ")",
+                _ => "}",
+            };
+            if self.open != "(" {
+                self.elems.sort();
+                self.elems.dedup();
+            }
+            let elems = self.elems.join(if self.open == "(" { ", " } else { " | " });
+            let ty = format!("{}{}{}", self.open, elems, close);
+            if self.name.is_empty() {
+                Ok(ty)
+            } else {
+                self.recorder.record(self.name.clone(), ty);
+                Ok(self.name)
+            }
+        }
+    }
+
+    impl<'a> ser::Serializer for &'a mut Recorder {
+        type Ok = String;
+        type Error = Error;
+        type SerializeSeq = Compound<'a>;
+        type SerializeTuple = Compound<'a>;
+        type SerializeTupleStruct = Compound<'a>;
+        type SerializeTupleVariant = Compound<'a>;
+        type SerializeMap = Compound<'a>;
+        type SerializeStruct = Compound<'a>;
+        type SerializeStructVariant = Compound<'a>;
+
+        fn serialize_bool(self, _: bool) -> Result<String, Error> {
+            Ok("bool".into())
+        }
+        fn serialize_i8(self, _: i8) -> Result<String, Error> {
+            Ok("i8".into())
+        }
+        fn serialize_i16(self, _: i16) -> Result<String, Error> {
+            Ok("i16".into())
+        }
+        fn serialize_i32(self, _: i32) -> Result<String, Error> {
+            Ok("i32".into())
+        }
+        fn serialize_i64(self, _: i64) -> Result<String, Error> {
+            Ok("i64".into())
+        }
+        fn serialize_u8(self, _: u8) -> Result<String, Error> {
+            Ok("u8".into())
+        }
+        fn serialize_u16(self, _: u16) -> Result<String, Error> {
+            Ok("u16".into())
+        }
+        fn serialize_u32(self, _: u32) -> Result<String, Error> {
+            Ok("u32".into())
+        }
+        fn serialize_u64(self, _: u64) -> Result<String, Error> {
+            Ok("u64".into())
+        }
+        fn serialize_u128(self, _: u128) -> Result<String, Error> {
+            Ok("u128".into())
+        }
+        fn serialize_f32(self, _: f32) -> Result<String, Error> {
+            Ok("f32".into())
+        }
+        fn serialize_f64(self, _: f64) -> Result<String, Error> {
+            Ok("f64".into())
+        }
+        fn serialize_char(self, _: char) -> Result<String, Error> {
+            Ok("char".into())
+        }
+        fn serialize_str(self, _: &str) -> Result<String, Error> {
+            Ok("str".into())
+        }
+        fn serialize_bytes(self, _: &[u8]) -> Result<String, Error> {
+            Ok("bytes".into())
+        }
+        fn serialize_none(self) -> Result<String, Error> {
+            Ok("none".into())
+        }
+        fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, Error> {
+            Ok(format!("some {}", self.describe(value)?))
+        }
+        fn serialize_unit(self) -> Result<String, Error> {
+            Ok("()".into())
+        }
+        fn serialize_unit_struct(self, name: &'static str) -> Result<String, Error> {
+            Ok(name.into())
+        }
+        fn serialize_unit_variant(
+            self,
+            name: &'static str,
+            _: u32,
+            variant: &'static str,
+        ) -> Result<String, Error> {
+            self.record(format!("{}::{}", name, variant), "()".into());
+            Ok(name.into())
+        }
+        fn serialize_newtype_struct<T: Serialize + ?Sized>(
+            self,
+            name: &'static str,
+            value: &T,
+        ) -> Result<String, Error> {
+            let ty = self.describe(value)?;
+            self.record(name.into(), ty);
+            Ok(name.into())
+        }
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(
+            self,
+            name: &'static str,
+            _: u32,
+            variant: &'static str,
+            value: &T,
+        ) -> Result<String, Error> {
+            let ty = self.describe(value)?;
+            self.record(format!("{}::{}", name, variant), ty);
+            Ok(name.into())
+        }
+        fn serialize_seq(self, _: Option<usize>) -> Result<Compound<'a>, Error> {
+            Ok(Compound::new(self, String::new(), "["))
+        }
+        fn serialize_tuple(self, _: usize) -> Result<Compound<'a>, Error> {
+            Ok(Compound::new(self, String::new(), "("))
+        }
+        fn serialize_tuple_struct(
+            self,
+            name: &'static str,
+            _: usize,
+        ) -> Result<Compound<'a>, Error> {
+            Ok(Compound::new(self, name.into(), "("))
+        }
+        fn serialize_tuple_variant(
+            self,
+            name: &'static str,
+            _: u32,
+            variant: &'static str,
+            _: usize,
+        ) -> Result<Compound<'a>, Error> {
+            Ok(Compound::new(self, format!("{}::{}", name, variant), "("))
+        }
+        fn serialize_map(self, _: Option<usize>) -> Result<Compound<'a>, Error> {
+            Ok(Compound::new(self, String::new(), "{"))
+        }
+        fn serialize_struct(self, name: &'static str, _: usize) -> Result<Compound<'a>, Error> {
+            Ok(Compound::new(self, name.into(), "{"))
+        }
+        fn serialize_struct_variant(
+            self,
+            name: &'static str,
+            _: u32,
+            variant: &'static str,
+            _: usize,
+        ) -> Result<Compound<'a>, Error> {
+            Ok(Compound::new(self, format!("{}::{}", name, variant), "{"))
+        }
+    }
+
+    impl ser::SerializeSeq for Compound<'_> {
+        type Ok = String;
+        type Error = Error;
+        fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+            self.elem(value)
+        }
+        fn end(self) -> Result<String, Error> {
+            self.collection()
+        }
+    }
+    impl ser::SerializeTuple for Compound<'_> {
+        type Ok = String;
+        type Error = Error;
+        fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+            self.elem(value)
+        }
+        fn end(self) -> Result<String, Error> {
+            self.collection()
+        }
+    }
+    impl ser::SerializeTupleStruct for Compound<'_> {
+        type Ok = String;
+        type Error = Error;
+        fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+            self.elem(value)
+        }
+        fn end(self) -> Result<String, Error> {
+            self.collection()
+        }
+    }
+    impl ser::SerializeTupleVariant for Compound<'_> {
+        type Ok = String;
+        type Error = Error;
+        fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+            self.elem(value)
+        }
+        fn end(self) -> Result<String, Error> {
+            let name = self.name.split("::").next().unwrap().to_string();
+            self.collection()?;
+            Ok(name)
+        }
+    }
+    impl ser::SerializeMap for Compound<'_> {
+        type Ok = String;
+        type Error = Error;
+        fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+            self.key = self.recorder.describe(key)?;
+            Ok(())
+        }
+        fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+            let ty = self.recorder.describe(value)?;
+            self.elems.push(format!("{}: {}", self.key, ty));
+            Ok(())
+        }
+        fn end(self) -> Result<String, Error> {
+            self.collection()
+        }
+    }
+    impl ser::SerializeStruct for Compound<'_> {
+        type Ok = String;
+        type Error = Error;
+        fn serialize_field<T: Serialize + ?Sized>(
+            &mut self,
+            key: &'static str,
+            value: &T,
+        ) -> Result<(), Error> {
+            self.field(key, value)
+        }
+        fn skip_field(&mut self, key: &'static str) -> Result<(), Error> {
+            self.recorder
+                .record(format!("{}.{}", self.name, key), "skipped".into());
+            Ok(())
+        }
+        fn end(self) -> Result<String, Error> {
+            Ok(self.name)
+        }
+    }
+    impl ser::SerializeStructVariant for Compound<'_> {
+        type Ok = String;
+        type Error = Error;
+        fn serialize_field<T: Serialize + ?Sized>(
+            &mut self,
+            key: &'static str,
+            value: &T,
+        ) -> Result<(), Error> {
+            self.field(key, value)
+        }
+        fn end(self) -> Result<String, Error> {
+            Ok(self.name.split("::").next().unwrap().to_string())
+        }
+    }
+}
+
+#[test]
+fn cache_format_is_bumped_when_the_model_changes() {
+    let db =
+        barostuff::parse_db(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/game"));
+    let mut recorder = shape::Recorder::default();
+    serde::Serialize::serialize(&db, &mut recorder).unwrap();
+    let mut described = format!("format {}\n", barostuff::CACHE_FORMAT);
+    for (key, types) in &recorder.fields {
+        // an empty list or map says nothing about its elements
+        let types = types
+            .iter()
+            .filter(|t| types.len() == 1 || !matches!(t.as_str(), "[]" | "{}"))
+            .map(|t| t.as_str())
+            .collect::<Vec<_>>();
+        described += &format!("{}: {}\n", key, types.join(" | "));
+    }
+
+    let shape_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cache_shape.txt");
+    let stored = std::fs::read_to_string(&shape_path).unwrap_or_default();
+    if stored == described {
+        return;
+    }
+    let stored_format = stored.lines().next().unwrap_or_default();
+    let format = described.lines().next().unwrap();
+    if stored_format == format {
+        panic!(
+            "the serialized model changed, bump CACHE_FORMAT in src/cache.rs \
+             and run with UPDATE_SNAPSHOTS=1"
+        );
+    }
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&shape_path, described).unwrap();
+        return;
+    }
+    panic!("CACHE_FORMAT changed, run with UPDATE_SNAPSHOTS=1 to update tests/cache_shape.txt");
+}
@@ -0,0 +1,111 @@
+use barostuff::cost::{arbitrage, crafting_costs, Route};
+use barostuff::{Item, LocationType};
+
+fn item(id: &str, price: i32, decon: &[(&str, i32)]) -> Item {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "name": id,
+        "tags": [],
+        "prices": { "base_price": price, "locations": { "outpost": [1.0, true] } },
+        "fabricate": null,
+        "deconstruct": { "time": 1, "mats": decon },
+        "has_inventory_icon": false,
+        "has_sprite": false,
+        "level_resource": null,
+        "unlocks_recipes": [],
+    }))
+    .unwrap()
+}
+
+// item with a recipe needing one of `tag`, or a raw material if `tag` is None
+fn crafted(id: &str, price: i32, tags: &[&str], tag: Option<&str>) -> Item {
+    let mut value = serde_json::to_value(item(id, price, &[])).unwrap();
+    value["tags"] = serde_json::json!(tags);
+    if let Some(tag) = tag {
+        value["fabricate"] = serde_json::json!({
+            "out_amount": 1,
+            "skills": [],
+            "time": 1,
+            "mats": [[{ "Tag": tag }, 1]],
+            "fabricator": "fabricator",
+            "requires_recipe": false,
+            "unlocked_by": [],
+        });
+    }
+    serde_json::from_value(value).unwrap()
+}
+
+fn outpost(sell_price_modifier: f32) -> Vec<LocationType> {
+    vec![LocationType {
+        id: "outpost".into(),
+        name: None,
+        sell_price_modifier,
+    }]
+}
+
+#[test]
+fn positive_margin_is_not_a_loop_at_buy_back_price() {
+    // scrap deconstructs into more than it costs, but the store only pays 30% for the gold
+    let items = [item("scrap", 10, &[("gold", 1)]), item("gold", 20, &[])];
+    let margins = arbitrage(&items, &outpost(0.3));
+    let scrap = margins
+        .iter()
+        .find(|m| m.item_id == "scrap" && m.route == Route::BuyDeconstruct)
+        .unwrap();
+    assert_eq!(scrap.margin(), 10.0);
+    assert_eq!(scrap.revenue, 6.0);
+    assert!(!scrap.is_money_loop());
+
+    // a store paying full price can be drained
+    let margins = arbitrage(&items, &outpost(1.0));
+    assert!(margins
+        .iter()
+        .any(|m| m.item_id == "scrap" && m.is_money_loop()));
+}
+
+#[test]
+fn unknown_location_types_use_default_modifier() {
+    let items = [item("scrap", 10, &[("gold", 1)]), item("gold", 20, &[])];
+    let margins = arbitrage(&items, &[]);
+    assert_eq!(
+        margins[0].revenue,
+        20.0 * LocationType::DEFAULT_SELL_PRICE_MODIFIER
+    );
+}
+
+#[test]
+fn deep_tag_recipes_are_computed_once() {
+    // every level can be made from any of the three items of the level below,
+    // which is 3^40 paths if sub-recipes aren't cached
+    let mut items = vec![];
+    for level in 0..40 {
+        for i in 0..3 {
+            let below = format!("level{}", level + 1);
+            items.push(crafted(
+                &format!("item{}_{}", level, i),
+                1000,
+                &[&format!("level{}", level)],
+                Some(&below),
+            ));
+        }
+    }
+    items.push(crafted("ore", 2, &["level40"], None));
+    let costs = crafting_costs(&items);
+    assert_eq!(costs["item0_0"].raw_cost, 2.0);
+    assert_eq!(costs["item0_0"].cheapest_cost, 2.0);
+    assert_eq!(costs["item0_0"].raw_mats["ore"], 1.0);
+}
+
+#[test]
+fn recipe_cycles_are_bought() {
+    // a needs b, b needs a or ore
+    let items = [
+        crafted("a", 50, &["x"], Some("y")),
+        crafted("b", 40, &["y"], Some("x")),
+        crafted("ore", 5, &["x"], None),
+    ];
+    let costs = crafting_costs(&items);
+    assert_eq!(costs["b"].cheapest_cost, 5.0);
+    assert_eq!(costs["a"].cheapest_cost, 5.0);
+    assert_eq!(costs["a"].raw_cost, 5.0);
+}
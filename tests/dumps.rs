@@ -0,0 +1,90 @@
+// Runs the binary on the fixture game and compares everything it writes to `out/` with `tests/snapshots`.
+// Set UPDATE_SNAPSHOTS=1 to accept the new output instead.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn files_in(dir: &Path) -> BTreeSet<String> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect(),
+        Err(_) => BTreeSet::new(),
+    }
+}
+
+#[test]
+fn dumps_match_snapshots() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let work_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("dumps");
+    let _ = std::fs::remove_dir_all(&work_dir);
+    std::fs::create_dir_all(&work_dir).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_barostuff"))
+        .arg("--game")
+        .arg(root.join("tests/fixtures/game"))
+        .current_dir(&work_dir)
+        .env("RUST_LOG", "warn")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let out_dir = work_dir.join("out");
+    let snapshot_dir = root.join("tests/snapshots");
+    let produced = files_in(&out_dir);
+    assert!(!produced.is_empty());
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        let _ = std::fs::remove_dir_all(&snapshot_dir);
+        std::fs::create_dir_all(&snapshot_dir).unwrap();
+        for name in &produced {
+            std::fs::copy(out_dir.join(name), snapshot_dir.join(name)).unwrap();
+        }
+        return;
+    }
+
+    assert_eq!(
+        produced,
+        files_in(&snapshot_dir),
+        "set of dumped files changed"
+    );
+    let mut mismatched = vec![];
+    for name in &produced {
+        let actual = std::fs::read_to_string(out_dir.join(name)).unwrap();
+        let expected = std::fs::read_to_string(snapshot_dir.join(name)).unwrap();
+        if actual != expected {
+            mismatched.push(name.clone());
+        }
+    }
+    assert!(
+        mismatched.is_empty(),
+        "dumps differ from snapshots: {:?}; compare {} with {}",
+        mismatched,
+        out_dir.display(),
+        snapshot_dir.display()
+    );
+}
+
+#[test]
+fn filtered_dumps_keep_recipe_materials() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let work_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("filtered_dumps");
+    let _ = std::fs::remove_dir_all(&work_dir);
+    std::fs::create_dir_all(&work_dir).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_barostuff"))
+        .arg("--game")
+        .arg(root.join("tests/fixtures/game"))
+        .args(["--no-cache", "--filter", "id:alloy", "--dumps", "fabricate"])
+        .current_dir(&work_dir)
+        .env("RUST_LOG", "warn")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let fabricate = std::fs::read_to_string(work_dir.join("out/fabricate_fabricator.txt")).unwrap();
+    // only the alloy row, but its ores are still listed although they don't match the filter
+    assert!(fabricate.contains("{{Hyperlink|Iron|30px}} or {{Hyperlink|Copper|30px}} (x2)"));
+    assert!(!fabricate.contains("[[Steel Bar]]"));
+}
@@ -0,0 +1,113 @@
+use barostuff::filter::{Filter, FilterError};
+use barostuff::Db;
+
+use std::path::Path;
+
+fn fixture_db() -> Db {
+    barostuff::parse_db(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/game"))
+}
+
+fn selected(db: &mut Db, expr: &str) -> Vec<String> {
+    db.select(&expr.parse().unwrap());
+    db.selected_items().map(|it| it.id.clone()).collect()
+}
+
+#[test]
+fn terms() {
+    let mut db = fixture_db();
+    assert_eq!(selected(&mut db, "tag:ore"), ["iron", "copper"]);
+    assert_eq!(selected(&mut db, "id:steel"), ["steel"]);
+    assert_eq!(selected(&mut db, "name:WIRE"), ["wire", "redwire"]);
+    assert_eq!(selected(&mut db, "name:\"steel bar\""), ["steel"]);
+    assert_eq!(
+        selected(&mut db, "id:\"wire\" or tag:\"ore\""),
+        ["wire", "iron", "copper"]
+    );
+    assert_eq!(
+        selected(&mut db, "fabricator:medicalfabricator"),
+        ["bandage"]
+    );
+    assert_eq!(
+        selected(&mut db, "sold:city"),
+        ["copper", "alloy", "bandage"]
+    );
+    assert_eq!(selected(&mut db, "has:level_resource"), ["iron", "copper"]);
+    assert_eq!(selected(&mut db, "price>=40"), ["steel", "alloy", "wrench"]);
+    assert_eq!(
+        selected(&mut db, "price != 10 and price<20"),
+        ["slag", "wire", "redwire", "copper"]
+    );
+}
+
+#[test]
+fn precedence() {
+    let mut db = fixture_db();
+    // and binds tighter than or
+    assert_eq!(
+        selected(&mut db, "tag:ore and price>10 or id:wrench"),
+        ["copper", "wrench"]
+    );
+    assert_eq!(
+        selected(&mut db, "tag:ore and (price>10 or id:wrench)"),
+        ["copper"]
+    );
+    assert_eq!(
+        selected(&mut db, "not has:fabricate and not tag:ore"),
+        ["slag"]
+    );
+    assert_eq!(
+        selected(&mut db, "not (has:fabricate or tag:ore)"),
+        ["slag"]
+    );
+    assert_eq!(selected(&mut db, "not not tag:ore"), ["iron", "copper"]);
+}
+
+#[test]
+fn selection_restricts_lists_but_not_lookups() {
+    let mut db = fixture_db();
+    db.select(&"fabricator:fabricator and price<=10".parse().unwrap());
+    let ids =
+        |items: Vec<&barostuff::Item>| items.iter().map(|it| it.id.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        ids(db.items_made_in("fabricator").collect()),
+        ["wire", "redwire"]
+    );
+    // tags are looked up for recipe materials, which don't depend on the selection
+    assert_eq!(ids(db.items_with_tag("ore").collect()), ["iron", "copper"]);
+    assert!(db.is_selected("wire"));
+    assert!(!db.is_selected("steel"));
+    assert_eq!(db.item("steel").unwrap().id, "steel");
+}
+
+#[test]
+fn errors() {
+    let error = |expr: &str| expr.parse::<Filter>().unwrap_err();
+    assert_eq!(
+        error("tag:"),
+        FilterError("expected a value after tag:, found the end".into())
+    );
+    assert_eq!(
+        error("colour:red"),
+        FilterError("unknown term \"colour\"".into())
+    );
+    assert_eq!(
+        error("price>cheap"),
+        FilterError("\"cheap\" is not a number".into())
+    );
+    assert_eq!(
+        error("(tag:ore"),
+        FilterError("expected \")\", found the end".into())
+    );
+    assert_eq!(
+        error("tag:ore tag:wire"),
+        FilterError("unexpected \"tag\"".into())
+    );
+    assert_eq!(
+        error("name:\"steel bar"),
+        FilterError("unterminated quote".into())
+    );
+    assert_eq!(
+        error("has:wings").to_string(),
+        "invalid filter: unknown part \"wings\""
+    );
+}
@@ -0,0 +1,81 @@
+use barostuff::graph::{EdgeKind, RecipeGraph};
+use barostuff::Item;
+
+fn item(id: &str, fab: &[&str], decon: &[&str]) -> Item {
+    let fabricate = if fab.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::json!({
+            "out_amount": 1,
+            "skills": [],
+            "time": 1,
+            "mats": fab
+                .iter()
+                .map(|id| serde_json::json!([{ "Id": id }, 1]))
+                .collect::<Vec<_>>(),
+            "fabricator": "fabricator",
+            "requires_recipe": false,
+            "unlocked_by": [],
+        })
+    };
+    let deconstruct = if decon.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::json!({ "time": 1, "mats": decon.iter().map(|id| (id, 1)).collect::<Vec<_>>() })
+    };
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "name": null,
+        "tags": [],
+        "prices": { "base_price": 10, "locations": {} },
+        "fabricate": fabricate,
+        "deconstruct": deconstruct,
+        "has_inventory_icon": false,
+        "has_sprite": false,
+        "level_resource": null,
+        "unlocks_recipes": [],
+    }))
+    .unwrap()
+}
+
+// ore -> bar -> tool, the tool deconstructs back into a bar, and a gem nothing is made of
+fn graph() -> RecipeGraph {
+    RecipeGraph::build(&[
+        item("ore", &[], &[]),
+        item("bar", &["ore"], &[]),
+        item("tool", &["bar"], &["bar"]),
+        item("gem", &[], &[]),
+    ])
+}
+
+#[test]
+fn subtree_keeps_what_is_reachable() {
+    let sub = graph().subtree("bar");
+    assert_eq!(sub.nodes.keys().collect::<Vec<_>>(), ["bar", "tool"]);
+    let edges = sub
+        .edges
+        .iter()
+        .map(|e| (e.from.as_str(), e.to.as_str(), e.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        [
+            ("bar", "tool", EdgeKind::Fabricate),
+            ("tool", "bar", EdgeKind::Deconstruct)
+        ]
+    );
+
+    let sub = graph().subtree("ore");
+    assert_eq!(sub.nodes.len(), 3);
+    assert_eq!(sub.edges.len(), 3);
+    let sub = graph().subtree("gem");
+    assert_eq!(sub.nodes.keys().collect::<Vec<_>>(), ["gem"]);
+    assert!(sub.edges.is_empty());
+}
+
+#[test]
+fn subtree_of_unknown_root_is_empty() {
+    let sub = graph().subtree("unobtainium");
+    assert!(sub.nodes.is_empty());
+    assert!(sub.edges.is_empty());
+}
@@ -0,0 +1,94 @@
+use barostuff::lint::{lint, Severity};
+
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn fixture_game_lint() {
+    let issues = lint(&fixture("game"), None);
+    // apart from tags the game code checks for, only the deliberate duplicate iron
+    // and the unnamed slag, in both its definitions. the overrides are fine
+    let issues = issues
+        .iter()
+        .filter(|i| i.check != "unused-tag")
+        .map(|i| (i.check, i.message.split(' ').next().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        issues,
+        [
+            ("missing-name", "slag"),
+            ("duplicate-identifier", "iron"),
+            ("missing-name", "slag"),
+            ("duplicate-identifier", "iron")
+        ]
+    );
+}
+
+#[test]
+fn finds_every_kind_of_issue() {
+    let issues = lint(&fixture("lint_game"), None);
+    let found = issues
+        .iter()
+        .map(|i| (i.line, i.severity, i.check))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (2, Severity::Error, "duplicate-identifier"),
+            (3, Severity::Warning, "unknown-attribute"),
+            (3, Severity::Warning, "unknown-attribute"),
+            (5, Severity::Info, "unused-tag"),
+            (6, Severity::Warning, "price-without-locations"),
+            (7, Severity::Error, "dangling-reference"),
+            (7, Severity::Error, "dangling-reference"),
+            (8, Severity::Error, "dangling-reference"),
+            (10, Severity::Warning, "missing-name"),
+            (13, Severity::Error, "duplicate-identifier"),
+        ]
+    );
+    assert!(issues
+        .iter()
+        .all(|i| i.file.ends_with("Content/Items/broken.xml")));
+    assert!(issues[5].message.contains("unobtainium"));
+    assert!(issues[6].message.contains("nosuchtag"));
+}
+
+#[test]
+fn mod_without_localization() {
+    let issues = lint(&fixture("lint_mod"), None);
+    // no missing-name without texts to look names up in,
+    // and two definitions on one line are still two definitions.
+    // a file that isn't utf-8 is reported, and the vanilla items the recipes need are unknown.
+    // upgrade price attributes are read ignoring case
+    let found = issues
+        .iter()
+        .map(|i| (i.line, i.column, i.check))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (1, 1, "unreadable-file"),
+            (2, 3, "duplicate-identifier"),
+            (2, 97, "duplicate-identifier"),
+            (4, 67, "dangling-reference"),
+            (4, 101, "dangling-reference"),
+            (5, 27, "dangling-reference"),
+            (3, 28, "unknown-attribute")
+        ]
+    );
+    assert!(issues[0].file.ends_with("latin1.xml"));
+    assert!(issues[6].message.contains("discount"));
+}
+
+#[test]
+fn mod_against_base_game() {
+    // the vanilla steel, iron and ore tag come from the base game
+    let issues = lint(&fixture("lint_mod"), Some(&fixture("game")));
+    assert!(issues.iter().all(|i| i.check != "dangling-reference"));
+    assert_eq!(issues.len(), 4);
+}
@@ -0,0 +1,281 @@
+use barostuff::{Db, Item, LevelGenerationParams, LevelResource, RecipeUnlock, RequiredItem};
+
+use std::path::Path;
+
+fn fixture_db() -> Db {
+    barostuff::parse_db(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/game"))
+}
+
+fn item<'a>(db: &'a Db, id: &str) -> &'a Item {
+    db.item(id).unwrap()
+}
+
+#[test]
+fn version() {
+    assert_eq!(fixture_db().version, "1.0.0.0");
+}
+
+#[test]
+fn items_from_all_files() {
+    let db = fixture_db();
+    let ids = db.items.iter().map(|it| it.id.as_str()).collect::<Vec<_>>();
+    // sorted by file path, then in file order. the slag is where its override is
+    assert_eq!(
+        ids,
+        ["slag", "wire", "redwire", "iron", "copper", "steel", "alloy", "bandage", "wrench"]
+    );
+    assert_eq!(item(&db, "steel").name.as_deref(), Some("Steel Bar"));
+}
+
+#[test]
+fn prices() {
+    let db = fixture_db();
+    let iron = &item(&db, "iron").prices;
+    assert_eq!(iron.base_price, 10);
+    assert_eq!(iron.at("outpost"), (1.0, true));
+    assert_eq!(iron.at("city"), (1.5, false));
+    assert_eq!(iron.at("mine"), (0.5, true));
+    // no entry, not sold
+    assert_eq!(iron.at("none"), (1.0, false));
+    assert_eq!(
+        item(&db, "wrench").prices.price_at("outpost").round(),
+        120.0
+    );
+}
+
+#[test]
+fn fabricate() {
+    let db = fixture_db();
+    let steel = item(&db, "steel").fabricate.as_ref().unwrap();
+    assert_eq!(steel.out_amount, 2);
+    assert_eq!(steel.time, 10);
+    assert_eq!(steel.fabricator, "fabricator");
+    assert_eq!(steel.skills, [("mechanical".to_string(), 20)]);
+    // repeated <RequiredItem>s are merged
+    assert_eq!(steel.mats, [(RequiredItem::Id("iron".into()), 2)]);
+
+    let bandage = item(&db, "bandage").fabricate.as_ref().unwrap();
+    assert_eq!(bandage.fabricator, "medicalfabricator");
+
+    let wrench = item(&db, "wrench").fabricate.as_ref().unwrap();
+    assert_eq!(
+        wrench.mats,
+        [
+            (RequiredItem::Id("steel".into()), 1),
+            (RequiredItem::Tag("wire".into()), 1)
+        ]
+    );
+    assert!(wrench.requires_recipe);
+    assert_eq!(
+        wrench.unlocked_by,
+        [RecipeUnlock::Talent("steelworker".into())]
+    );
+}
+
+#[test]
+fn deconstruct() {
+    let db = fixture_db();
+    let wrench = item(&db, "wrench").deconstruct.as_ref().unwrap();
+    assert_eq!(wrench.time, 10);
+    assert_eq!(wrench.mats, [("steel".to_string(), 3)]);
+    assert!(item(&db, "iron")
+        .deconstruct
+        .as_ref()
+        .unwrap()
+        .mats
+        .is_empty());
+    assert!(item(&db, "copper").deconstruct.is_none());
+}
+
+#[test]
+fn tags_and_usages() {
+    let db = fixture_db();
+    assert_eq!(item(&db, "bandage").tags, ["smallitem", "medical"]);
+    // the wrench takes any wire, so both wires are used in it
+    assert_eq!(db.used_in("redwire"), [("wrench", 1)]);
+    assert_eq!(db.used_in("wire"), [("wrench", 1)]);
+    assert_eq!(
+        db.used_in("copper"),
+        [("alloy", 2), ("bandage", 1), ("redwire", 1), ("wire", 1)]
+    );
+    assert_eq!(db.obtained_from("steel"), [("wrench", 3)]);
+}
+
+#[test]
+fn level_resources() {
+    let db = fixture_db();
+    let iron = item(&db, "iron").level_resource.as_ref().unwrap();
+    assert_eq!(iron.comonness_default, 0.5);
+    assert_eq!(iron.comonness["ridgebasic"], 0.2);
+    assert_eq!(db.biome_commonness(iron, "europanridge"), Some(0.2));
+    // no level type specific entry, falls back to the default
+    assert_eq!(db.biome_commonness(iron, "coldcaverns"), Some(0.5));
+    // no level can be generated in the end zone
+    assert_eq!(db.biome_commonness(iron, "endzone"), None);
+    assert!(item(&db, "steel").level_resource.is_none());
+}
+
+#[test]
+fn biome_commonness_doesnt_depend_on_order() {
+    let mut db = fixture_db();
+    let level_params = |id: &str, biome: &str| LevelGenerationParams {
+        id: id.into(),
+        biomes: vec![biome.into()],
+    };
+    // ahead of the biome specific ones, which it must not shadow
+    db.level_params.insert(0, level_params("anybasic", "any"));
+    db.level_params
+        .push(level_params("ridgedeep", "europanridge"));
+    let lr = LevelResource {
+        comonness_default: 0.5,
+        comonness: [("anybasic", 0.9), ("ridgebasic", 0.2), ("ridgedeep", 0.3)]
+            .iter()
+            .map(|(id, com)| (id.to_string(), *com))
+            .collect(),
+    };
+    // the highest of the biome's level types
+    assert_eq!(db.biome_commonness(&lr, "europanridge"), Some(0.3));
+    // no entry for the biome's own level type, the one for any biome applies
+    assert_eq!(db.biome_commonness(&lr, "coldcaverns"), Some(0.9));
+    // levels that can be anywhere can be in the end zone too
+    assert_eq!(db.biome_commonness(&lr, "endzone"), Some(0.9));
+}
+
+#[test]
+fn localization() {
+    let db = fixture_db();
+    assert_eq!(db.localization.item_name_opt("iron"), Some("Iron"));
+    assert_eq!(
+        db.localization.item_description("bandage"),
+        Some("Stops bleeding.")
+    );
+    assert_eq!(db.localization.item_description("steel"), None);
+    assert_eq!(db.localization.skill_name("medical"), "Medical");
+    // missing texts fall back to the id
+    assert_eq!(db.localization.skill_name("helm"), "helm");
+}
+
+#[test]
+fn other_content() {
+    let db = fixture_db();
+    let crawler = db.creatures.iter().find(|c| c.id == "crawler").unwrap();
+    assert_eq!(crawler.health, Some(80.0));
+    assert_eq!(crawler.inventory[0], ("iron".to_string(), 2));
+    assert_eq!(db.jobs[0].id, "engineer");
+    assert_eq!(db.talent_name("steelworker"), "Steelworker");
+    // the stat with a value that isn't a number is skipped
+    let toughness = db.talents.iter().find(|t| t.id == "toughness").unwrap();
+    assert_eq!(
+        toughness.stats,
+        [("MaximumHealthMultiplier".to_string(), 0.1)]
+    );
+    assert_eq!(db.missions.len(), 3);
+    // every item once, even when the duplicates aren't next to each other
+    assert_eq!(db.missions[2].required_items, ["steel", "wrench"]);
+    assert_eq!(db.submarines[0].name, "Dugong");
+    assert_eq!(db.submarines[0].items[2], ("wrench".to_string(), 2));
+    // the gzipped one is read too, the malformed Broken.sub is skipped
+    assert_eq!(db.submarines.len(), 2);
+    assert_eq!(db.submarines[1].name, "Humpback");
+    assert_eq!(db.submarines[1].dimensions, Some((3000.0, 1100.0)));
+    assert_eq!(db.submarines[1].items[0], ("bandage".to_string(), 2));
+    assert_eq!(db.upgrades[0].max_level_for(Some("scout")), 3);
+    // the broken one is skipped
+    assert_eq!(db.upgrades.len(), 1);
+    assert_eq!(db.event_location_types("givewrench"), Some(vec!["outpost"]));
+    assert_eq!(db.outposts.len(), 2);
+    let sell_price_modifiers = db
+        .location_types
+        .iter()
+        .map(|lt| (lt.id.as_str(), lt.sell_price_modifier))
+        .collect::<Vec<_>>();
+    assert_eq!(sell_price_modifiers[0], ("outpost", 0.3));
+    assert_eq!(sell_price_modifiers[4], ("mine", 0.5));
+}
+
+#[test]
+fn upgrade_prices() {
+    let db = fixture_db();
+    let price = &db.upgrades[0].price;
+    // +10% at the first level, then a fifth of the way to +100% more per level
+    let prices = (1..=5).map(|l| price.at_level(l, 5)).collect::<Vec<_>>();
+    assert_eq!(prices, [1100, 1280, 1460, 1640, 1820]);
+    assert_eq!(price.at_level(1, 3), 1100);
+    assert_eq!(price.at_level(3, 3), 1700);
+}
+
+#[test]
+fn duplicates_and_overrides() {
+    let db = fixture_db();
+    // the override replaces the first definition in place, a plain duplicate is ignored
+    assert_eq!(item(&db, "redwire").prices.base_price, 6);
+    assert_eq!(item(&db, "iron").prices.base_price, 10);
+    assert_eq!(db.items.iter().filter(|it| it.id == "iron").count(), 1);
+    // a root <Override> with the items right in it
+    assert_eq!(item(&db, "bandage").prices.base_price, 25);
+    // an override that sorts before the original still wins
+    assert_eq!(item(&db, "slag").prices.base_price, 2);
+
+    let dups = db
+        .duplicate_items
+        .iter()
+        .map(|d| {
+            (
+                d.id.as_str(),
+                d.kept.clone(),
+                d.ignored.clone(),
+                d.overridden,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dups,
+        [
+            (
+                "bandage",
+                "Content/Items/Medical/medical_override.xml".into(),
+                vec!["Content/Items/Medical/medical.xml".into()],
+                true
+            ),
+            (
+                "iron",
+                "Content/Items/Materials/materials.xml".into(),
+                vec!["Content/Items/Tools/tools_extra.xml".into()],
+                false
+            ),
+            (
+                "redwire",
+                "Content/Items/Tools/tools_extra.xml".into(),
+                vec!["Content/Items/Electrical/wires.xml".into()],
+                true
+            ),
+            (
+                "slag",
+                "Content/Items/Adjustments/slag_override.xml".into(),
+                vec!["Content/Items/Materials/materials.xml".into()],
+                true
+            ),
+        ]
+    );
+}
+
+#[test]
+fn item_lookup() {
+    let db = fixture_db();
+    fn ids<'a>(items: impl Iterator<Item = &'a Item>) -> Vec<&'a str> {
+        items.map(|it| it.id.as_str()).collect()
+    }
+    assert_eq!(db.item("steel").unwrap().id, "steel");
+    assert!(db.item("unobtainium").is_none());
+    assert_eq!(
+        db.try_item("unobtainium").unwrap_err().to_string(),
+        "unknown item \"unobtainium\""
+    );
+    assert_eq!(ids(db.items_with_tag("ore")), ["iron", "copper"]);
+    assert!(db.items_with_tag("nosuchtag").next().is_none());
+    assert_eq!(ids(db.items_made_in("medicalfabricator")), ["bandage"]);
+    assert_eq!(
+        ids(db.items_made_in("fabricator")),
+        ["wire", "redwire", "steel", "alloy", "wrench"]
+    );
+}
@@ -0,0 +1,86 @@
+// Runs `barostuff query` on the fixture game.
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn query(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_barostuff"))
+        .arg("--game")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/game"))
+        .arg("--no-cache")
+        .arg("query")
+        .args(args)
+        .env("RUST_LOG", "error")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn describes_item() {
+    let output = query(&["steel"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Steel Bar (steel)
+
+base price: 40
+prices:
+  Outpost: 40 (x1)
+fabricate: fabricator, 10 s, makes 2
+  Iron (iron) x2
+  needs Mechanical 20
+deconstruct: 10 s
+  Iron (iron) x2
+used in:
+  Wrench (wrench) x1
+obtained from:
+  Wrench (wrench) x3
+"
+    );
+}
+
+#[test]
+fn finds_item_by_name() {
+    let output = query(&["iron"]);
+    let by_name = query(&["IRON"]);
+    assert!(by_name.status.success());
+    assert_eq!(output.stdout, by_name.stdout);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Iron ore.\n"));
+    assert!(stdout.contains("  City: 15 (x1.5), not sold\n"));
+    assert!(stdout.contains("level resource: commonness 0.5\n  greatseabasic: 0.1\n"));
+}
+
+#[test]
+fn unknown_item() {
+    let output = query(&["unobtainium"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "unknown item \"unobtainium\"\n"
+    );
+}
+
+#[test]
+fn json() {
+    let output = query(&["--json", "bandage"]);
+    assert!(output.status.success());
+    let item: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    // the same description as the text output
+    assert_eq!(item["id"], "bandage");
+    assert_eq!(item["name"], "Bandage");
+    assert_eq!(item["fabricate"]["fabricator"], "medicalfabricator");
+    assert_eq!(
+        item["fabricate"]["mats"][0],
+        serde_json::json!({"id": "copper", "name": "Copper", "amount": 1})
+    );
+    assert_eq!(item["prices"][0]["location_name"], "City");
+
+    let output = query(&["--json", "steel"]);
+    let item: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        item["used_in"],
+        serde_json::json!([{"id": "wrench", "name": "Wrench", "amount": 1}])
+    );
+    assert_eq!(item["obtained_from"][0]["amount"], 3);
+}